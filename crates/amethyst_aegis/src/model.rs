@@ -1,3 +1,4 @@
+use amethyst_clock::Clock;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
@@ -18,11 +19,17 @@ pub struct ForensicEvent {
 }
 
 impl ForensicEvent {
-    pub fn record(subject: Uuid, decision: Decision) -> Self {
+    /// Record `decision`, stamped with a reading from `clock`.
+    pub fn record(subject: Uuid, decision: Decision, clock: &dyn Clock) -> Self {
         Self {
             subject,
             decision,
-            timestamp: Utc::now(),
+            timestamp: timestamp(clock),
         }
     }
 }
+
+/// A `clock` reading as a UTC timestamp.
+pub fn timestamp(clock: &dyn Clock) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(clock.now_ms() as i64).unwrap_or_default()
+}
@@ -0,0 +1,25 @@
+use amethyst_aegis::model::{self, Decision, ForensicEvent};
+use amethyst_clock::{RecordedClock, ReplayClock, StepClock, SystemClock};
+use uuid::Uuid;
+
+#[test]
+fn events_are_stamped_from_the_clock() {
+    let subject = Uuid::new_v4();
+    let event = ForensicEvent::record(subject, Decision::Allow, &StepClock::fixed(1700000000000));
+    assert_eq!(event.subject, subject);
+    assert_eq!(event.decision, Decision::Allow);
+    assert_eq!(event.timestamp.timestamp_millis(), 1700000000000);
+}
+
+#[test]
+fn replayed_clock_reissues_the_same_timestamps() {
+    let subject = Uuid::new_v4();
+    let live = RecordedClock::new(SystemClock);
+    let first = ForensicEvent::record(subject, Decision::Review, &live);
+    let second = ForensicEvent::record(subject, Decision::Deny, &live);
+
+    let replay = ReplayClock::new(live.readings());
+    assert_eq!(model::timestamp(&replay), first.timestamp);
+    assert_eq!(model::timestamp(&replay), second.timestamp);
+    assert!(!replay.overrun());
+}
@@ -0,0 +1,196 @@
+//! Amethyst Clock
+//!
+//! Injectable time sources. Code on a deterministic path (DET_001) never
+//! reads the wall clock itself: it takes a `Clock`, so the same run can be
+//! driven by the system clock live and by a scripted clock on replay.
+//!
+//! - `SystemClock`: wall-clock time
+//! - `StepClock`: fixed start, fixed increment per read (0 = frozen)
+//! - `RecordedClock`: wraps another clock and keeps every reading
+//! - `ReplayClock`: hands back recorded readings, in order
+//!
+//! Clocks are `Send + Sync`, so one `Arc<dyn Clock>` can be shared across
+//! threads (e.g. by an executor serving requests concurrently).
+//!
+//! Out of scope: the frozen `crates/aegis` (see its `FROZEN` marker and
+//! `AEGIS_SHA256SUMS.txt`) still reads `Utc::now()` in `aegis::evaluate`
+//! and is deliberately left untouched. It is not a workspace member;
+//! `amethyst_aegis` is the live crate and takes a `Clock`.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub trait Clock: Send + Sync {
+    /// Current time, Unix epoch milliseconds.
+    fn now_ms(&self) -> u64;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now_ms(&self) -> u64 {
+        (**self).now_ms()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now_ms(&self) -> u64 {
+        (**self).now_ms()
+    }
+}
+
+/// Wall-clock time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Starts at `start_ms` and moves `step_ms` forward on every read.
+/// A `step_ms` of 0 is a fixed clock.
+#[derive(Debug)]
+pub struct StepClock {
+    next_ms: AtomicU64,
+    step_ms: u64,
+}
+
+impl StepClock {
+    pub fn new(start_ms: u64, step_ms: u64) -> Self {
+        Self {
+            next_ms: AtomicU64::new(start_ms),
+            step_ms,
+        }
+    }
+
+    pub fn fixed(at_ms: u64) -> Self {
+        Self::new(at_ms, 0)
+    }
+}
+
+impl Clock for StepClock {
+    fn now_ms(&self) -> u64 {
+        let step = |now: u64| Some(now.saturating_add(self.step_ms));
+        // `step` never refuses, so both arms carry the previous value
+        self.next_ms
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, step)
+            .unwrap_or_else(|now| now)
+    }
+}
+
+/// Passes readings through from `inner` and keeps a copy of each.
+#[derive(Debug)]
+pub struct RecordedClock<C> {
+    inner: C,
+    readings: Mutex<Vec<u64>>,
+}
+
+impl<C: Clock> RecordedClock<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            readings: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Every reading so far, in order.
+    pub fn readings(&self) -> Vec<u64> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<u64>> {
+        // A reading is pushed whole or not at all: a poisoned lock is still consistent
+        self.readings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<C: Clock> Clock for RecordedClock<C> {
+    fn now_ms(&self) -> u64 {
+        let now = self.inner.now_ms();
+        self.lock().push(now);
+        now
+    }
+}
+
+/// Returns `readings` in order; once they run out, repeats the last one.
+///
+/// A faithful re-run reads the clock exactly as often as the recorded run,
+/// so it never reaches the end; `overrun` reports if it did.
+#[derive(Debug)]
+pub struct ReplayClock {
+    readings: Vec<u64>,
+    next: AtomicUsize,
+}
+
+impl ReplayClock {
+    pub fn new(readings: Vec<u64>) -> Self {
+        Self {
+            readings,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether more readings were taken than were recorded.
+    pub fn overrun(&self) -> bool {
+        self.next.load(Ordering::Relaxed) > self.readings.len()
+    }
+}
+
+impl Clock for ReplayClock {
+    fn now_ms(&self) -> u64 {
+        let next = |i: usize| Some(i.saturating_add(1));
+        let i = self
+            .next
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, next)
+            .unwrap_or_else(|i| i);
+        self.readings
+            .get(i)
+            .or(self.readings.last())
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_clock_advances_per_read() {
+        let clock = StepClock::new(100, 5);
+        assert_eq!(
+            (clock.now_ms(), clock.now_ms(), clock.now_ms()),
+            (100, 105, 110)
+        );
+        let frozen = StepClock::fixed(7);
+        assert_eq!((frozen.now_ms(), frozen.now_ms()), (7, 7));
+    }
+
+    #[test]
+    fn recorded_readings_replay_exactly() {
+        let live = RecordedClock::new(SystemClock);
+        let seen: Vec<u64> = (0..3).map(|_| live.now_ms()).collect();
+        assert_eq!(live.readings(), seen);
+
+        let replay = ReplayClock::new(live.readings());
+        let again: Vec<u64> = (0..3).map(|_| replay.now_ms()).collect();
+        assert_eq!(again, seen);
+        assert!(!replay.overrun());
+
+        assert_eq!(replay.now_ms(), seen[2]);
+        assert!(replay.overrun());
+    }
+
+    #[test]
+    fn shared_clocks_are_clocks() {
+        let shared: Arc<dyn Clock> = Arc::new(StepClock::new(0, 1));
+        let recorded = RecordedClock::new(shared.clone());
+        recorded.now_ms();
+        assert_eq!(shared.now_ms(), 1);
+        assert_eq!(recorded.readings(), vec![0]);
+    }
+}
@@ -114,3 +114,121 @@ pub const SYSTEM_INVARIANTS: &[Invariant] = &[
 /// Compile-time invariant count.
 /// Useful for audits and integrity checks.
 pub const INVARIANT_COUNT: usize = SYSTEM_INVARIANTS.len();
+
+/// Look up a system invariant by ID.
+pub fn invariant(id: &str) -> Option<&'static Invariant> {
+    SYSTEM_INVARIANTS.iter().find(|inv| inv.id == id)
+}
+
+// ─────────────────────────────────────────────
+// ENFORCEMENT COVERAGE
+// ─────────────────────────────────────────────
+
+/// A runtime check that upholds a system invariant.
+///
+/// Enforcing crates publish these as constants; the mapping is
+/// declared next to the check itself, not inferred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnforcementPoint {
+    /// `SYSTEM_INVARIANTS` ID this check upholds.
+    pub invariant_id: &'static str,
+    /// Path of the enforcing check (e.g. `pilgrim_mandate::Mandate::enforce`).
+    pub enforced_by: &'static str,
+}
+
+impl EnforcementPoint {
+    pub const fn new(invariant_id: &'static str, enforced_by: &'static str) -> Self {
+        Self {
+            invariant_id,
+            enforced_by,
+        }
+    }
+}
+
+/// An error raised by an enforcement point.
+///
+/// The invariant is read off the point that raised the error, so it can
+/// never disagree with the crate's `ENFORCEMENT` table.
+pub trait Enforced {
+    /// Enforcement point that raised this error.
+    fn enforcement_point(&self) -> &'static EnforcementPoint;
+
+    /// `SYSTEM_INVARIANTS` ID this failure upholds.
+    fn system_invariant(&self) -> &'static str {
+        self.enforcement_point().invariant_id
+    }
+}
+
+/// Coverage of `SYSTEM_INVARIANTS` by a set of enforcement points.
+///
+/// Built from the `ENFORCEMENT` tables published by each runtime crate.
+/// Iteration follows `SYSTEM_INVARIANTS` order.
+#[derive(Debug, Clone, Copy)]
+pub struct CoverageReport<'a> {
+    tables: &'a [&'a [EnforcementPoint]],
+}
+
+impl<'a> CoverageReport<'a> {
+    pub const fn new(tables: &'a [&'a [EnforcementPoint]]) -> Self {
+        Self { tables }
+    }
+
+    /// Every enforcement point upholding `invariant_id`.
+    pub fn enforced_by(
+        &self,
+        invariant_id: &'a str,
+    ) -> impl Iterator<Item = &'a EnforcementPoint> + 'a {
+        self.points().filter(move |p| p.invariant_id == invariant_id)
+    }
+
+    /// System invariants with at least one active enforcement point.
+    pub fn enforced(&self) -> impl Iterator<Item = &'static Invariant> + 'a {
+        let report = *self;
+        SYSTEM_INVARIANTS
+            .iter()
+            .filter(move |inv| report.enforced_by(inv.id).next().is_some())
+    }
+
+    /// System invariants that nothing enforces at runtime.
+    pub fn unenforced(&self) -> impl Iterator<Item = &'static Invariant> + 'a {
+        let report = *self;
+        SYSTEM_INVARIANTS
+            .iter()
+            .filter(move |inv| report.enforced_by(inv.id).next().is_none())
+    }
+
+    /// Enforcement points naming an ID that is not a system invariant.
+    pub fn unknown(&self) -> impl Iterator<Item = &'a EnforcementPoint> + 'a {
+        self.points().filter(|p| invariant(p.invariant_id).is_none())
+    }
+
+    fn points(&self) -> impl Iterator<Item = &'a EnforcementPoint> + 'a {
+        self.tables.iter().flat_map(|table| table.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUNTIME: &[EnforcementPoint] = &[
+        EnforcementPoint::new("SAFE_001", "runtime::halt"),
+        EnforcementPoint::new("TRANS_002", "runtime::sentinel"),
+        EnforcementPoint::new("SAFE_001", "runtime::deny"),
+    ];
+    const STRAY: &[EnforcementPoint] = &[EnforcementPoint::new("NOPE_001", "runtime::stray")];
+
+    #[test]
+    fn report_splits_enforced_and_unenforced() {
+        let report = CoverageReport::new(&[RUNTIME, STRAY]);
+
+        let mut enforced = report.enforced().map(|inv| inv.id);
+        assert_eq!(enforced.next(), Some("TRANS_002"));
+        assert_eq!(enforced.next(), Some("SAFE_001"));
+        assert_eq!(enforced.next(), None);
+
+        assert_eq!(report.unenforced().count(), INVARIANT_COUNT - 2);
+        assert_eq!(report.enforced_by("SAFE_001").count(), 2);
+        assert_eq!(report.unknown().next().map(|p| p.enforced_by), Some("runtime::stray"));
+    }
+}
@@ -12,7 +12,7 @@ fn main() {
     let mut state = State { value: 1 };
 
     // BEFORE execution snapshot
-    let token = Sentinel::before(&state, "STATE_V1");
+    let token = Sentinel::before(&state);
 
     // ⚠️ SILENT MUTATION (simulated attack)
     state.value = 999;
@@ -35,10 +35,7 @@ fn main() {
 // ---- Minimal stubs ----
 
 fn dummy_spec() -> InvariantSpec {
-    InvariantSpec {
-        id: "demo-invariant",
-        class: InvariantClass::Value,
-    }
+    InvariantSpec::new("demo-invariant", InvariantClass::Value).in_domain("demo")
 }
 
 fn dummy_ledger() -> DriftLedger {
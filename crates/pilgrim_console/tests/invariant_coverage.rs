@@ -0,0 +1,41 @@
+use amethyst_invariants::{CoverageReport, INVARIANT_COUNT, SYSTEM_INVARIANTS};
+
+/// Every `ENFORCEMENT` table in the runtime.
+const TABLES: &[&[amethyst_invariants::EnforcementPoint]] = &[
+    pilgrim_core::constraints::ENFORCEMENT,
+    pilgrim_core::engine::ENFORCEMENT,
+    pilgrim_core::replay::ENFORCEMENT,
+    pilgrim_handshake::schema::ENFORCEMENT,
+    pilgrim_mandate::ENFORCEMENT,
+    pilgrim_sentinel::ENFORCEMENT,
+];
+
+/// Invariants with no runtime check, by design rather than by omission.
+/// - DET_002: cartridges never see the caller's identity, so there is
+///   nothing to check at run time
+/// - AGENCY_001, AGENCY_002: about what the system presents to users;
+///   nothing in the runtime decides or persuades
+const UNENFORCED: &[&str] = &["DET_002", "AGENCY_001", "AGENCY_002"];
+
+#[test]
+fn every_system_invariant_is_enforced_or_listed() {
+    let report = CoverageReport::new(TABLES);
+
+    let unknown: Vec<_> = report.unknown().map(|p| p.enforced_by).collect();
+    assert!(unknown.is_empty(), "unknown invariant IDs: {unknown:?}");
+
+    let unenforced: Vec<_> = report.unenforced().map(|inv| inv.id).collect();
+    assert_eq!(
+        unenforced, UNENFORCED,
+        "coverage changed: update UNENFORCED"
+    );
+
+    assert_eq!(
+        report.enforced().count() + UNENFORCED.len(),
+        INVARIANT_COUNT
+    );
+    for inv in SYSTEM_INVARIANTS {
+        let points = report.enforced_by(inv.id).count();
+        assert_eq!(points == 0, UNENFORCED.contains(&inv.id), "{}", inv.id);
+    }
+}
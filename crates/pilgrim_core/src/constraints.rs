@@ -1,4 +1,19 @@
+use crate::engine;
 use crate::store::PrivacyTier;
+use amethyst_invariants::{Enforced, EnforcementPoint};
+
+const STEP_ALLOWED: EnforcementPoint = EnforcementPoint::new(
+    "SAFE_001",
+    "pilgrim_core::constraints::Constraints::assert_step_allowed",
+);
+const RUNTIME_ALLOWED: EnforcementPoint = EnforcementPoint::new(
+    "SAFE_001",
+    "pilgrim_core::constraints::Constraints::assert_runtime_allowed",
+);
+
+/// System invariants upheld by constraint checks.
+/// Unbounded compute is refused rather than attempted.
+pub const ENFORCEMENT: &[EnforcementPoint] = &[STEP_ALLOWED, RUNTIME_ALLOWED];
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Constraints {
@@ -56,4 +71,41 @@ pub enum ConstraintsError {
         max_runtime_ms: u64,
         elapsed_ms: u64,
     },
+    /// `require_logs` is set but no trace is attached.
+    TraceRequired,
+}
+
+impl Enforced for ConstraintsError {
+    fn enforcement_point(&self) -> &'static EnforcementPoint {
+        match self {
+            ConstraintsError::StepLimitExceeded { .. } => &STEP_ALLOWED,
+            ConstraintsError::RuntimeLimitExceeded { .. } => &RUNTIME_ALLOWED,
+            // Raised by the engine loop, not by `Constraints` itself
+            ConstraintsError::TraceRequired => &engine::TRACE_REQUIRED,
+        }
+    }
+}
+
+impl std::fmt::Display for ConstraintsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstraintsError::StepLimitExceeded {
+                max_steps,
+                attempted_step_index,
+            } => write!(
+                f,
+                "step limit exceeded: step {} of max {}",
+                attempted_step_index, max_steps
+            ),
+            ConstraintsError::RuntimeLimitExceeded {
+                max_runtime_ms,
+                elapsed_ms,
+            } => write!(
+                f,
+                "runtime limit exceeded: {} ms of max {} ms",
+                elapsed_ms, max_runtime_ms
+            ),
+            ConstraintsError::TraceRequired => write!(f, "logs required but no trace attached"),
+        }
+    }
 }
@@ -1,19 +1,144 @@
-#[derive(Default)]
+use crate::constraints::{Constraints, ConstraintsError};
+use crate::trace::Trace;
+use amethyst_clock::{Clock, SystemClock};
+use amethyst_invariants::EnforcementPoint;
+use std::sync::Arc;
+
+// Every tick is checked against step and runtime limits first
+const LIMITS: EnforcementPoint =
+    EnforcementPoint::new("SAFE_001", "pilgrim_core::engine::PilgrimEngine::advance");
+// `require_logs` runs only with a trace attached
+pub(crate) const TRACE_REQUIRED: EnforcementPoint =
+    EnforcementPoint::new("TRANS_001", "pilgrim_core::engine::PilgrimEngine::advance");
+
+/// System invariants upheld by the engine loop.
+pub const ENFORCEMENT: &[EnforcementPoint] = &[LIMITS, TRACE_REQUIRED];
+
+/// Tick loop, bounded by `Constraints`.
+///
+/// Runtime is measured from the first tick, through `Clock`; readings are
+/// kept in the attached trace, so a `ReplayClock` re-runs the same timings.
+/// Once a limit trips, the engine stays halted: the error is recorded in
+/// the attached trace and every later `advance` returns it again.
 pub struct PilgrimEngine {
     ticks: u64,
+    constraints: Constraints,
+    clock: Arc<dyn Clock>,
+    started_ms: Option<u64>,
+    trace: Option<Trace>,
+    halted: Option<ConstraintsError>,
+}
+
+impl Default for PilgrimEngine {
+    /// Unconstrained, on the system clock.
+    fn default() -> Self {
+        Self::new(Constraints::default(), Arc::new(SystemClock))
+    }
 }
 
 impl PilgrimEngine {
-    pub fn advance(&mut self, _event: &str) {
+    pub fn new(constraints: Constraints, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            ticks: 0,
+            constraints,
+            clock,
+            started_ms: None,
+            trace: None,
+            halted: None,
+        }
+    }
+
+    /// Attach the trace that receives recorded steps (and any halt).
+    pub fn with_trace(mut self, trace: Trace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Check every constraint, then count one tick.
+    ///
+    /// Returns the index of the tick that may now run.
+    pub fn advance(&mut self, _event: &str) -> Result<u64, ConstraintsError> {
+        if let Some(e) = &self.halted {
+            return Err(e.clone());
+        }
+        if self.constraints.require_logs && self.trace.is_none() {
+            return Err(self.halt(ConstraintsError::TraceRequired));
+        }
+
+        let now = self.now_ms();
+        let started = *self.started_ms.get_or_insert(now);
+        let checked = self
+            .constraints
+            .assert_step_allowed(self.ticks)
+            .and_then(|_| {
+                self.constraints
+                    .assert_runtime_allowed(now.saturating_sub(started))
+            });
+        if let Err(e) = checked {
+            return Err(self.halt(e));
+        }
+
+        let tick = self.ticks;
         self.ticks += 1;
+        Ok(tick)
     }
 
     // Alias required by tests / constraints layer
-    pub fn step(&mut self, event: &str) {
-        self.advance(event);
+    pub fn step(&mut self, event: &str) -> Result<u64, ConstraintsError> {
+        self.advance(event)
+    }
+
+    /// Record a step's payload in the attached trace (no-op without one).
+    pub fn record(&mut self, name: &str, payload: &[u8]) {
+        if let Some(trace) = &mut self.trace {
+            trace.push_step(name, payload);
+        }
+    }
+
+    /// Final runtime check, for time spent in the last tick.
+    pub fn finish(&mut self) -> Result<(), ConstraintsError> {
+        if let Some(e) = &self.halted {
+            return Err(e.clone());
+        }
+        let Some(started) = self.started_ms else {
+            return Ok(());
+        };
+        let elapsed = self.now_ms().saturating_sub(started);
+        self.constraints
+            .assert_runtime_allowed(elapsed)
+            .map_err(|e| self.halt(e))
     }
 
     pub fn ticks(&self) -> u64 {
         self.ticks
     }
+
+    pub fn halted(&self) -> Option<&ConstraintsError> {
+        self.halted.as_ref()
+    }
+
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    /// Read the clock; the reading goes into the trace for replay.
+    fn now_ms(&mut self) -> u64 {
+        let now = self.clock.now_ms();
+        if let Some(trace) = &mut self.trace {
+            trace.record_clock(now);
+        }
+        now
+    }
+
+    fn halt(&mut self, e: ConstraintsError) -> ConstraintsError {
+        if let Some(trace) = &mut self.trace {
+            trace.record_halt(e.clone());
+        }
+        self.halted = Some(e.clone());
+        e
+    }
 }
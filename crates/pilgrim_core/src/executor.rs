@@ -0,0 +1,281 @@
+//! End-to-end run: verified `RequestEnvelope` → cartridge → `Trace` →
+//! `Receipt` → sealed `ResponseEnvelope`.
+//!
+//! The intent names its run explicitly (nothing is inferred):
+//! - `cartridge` (text): id of a registered cartridge
+//! - `ticks` (int): how many ticks to drive, `1..=constraints.max_steps`
+//!
+//! plus whatever inputs the cartridge declared at registration. All of it
+//! is checked as one `InputSchema`, so a bad intent comes back `Rejected`
+//! with every violation listed, before any tick runs.
+//!
+//! The trace opens with an `inputs` step over the intent's inputs, then
+//! records one `{cartridge}:{tick}` step per tick. Step payloads are
+//! canonical CBOR, so `replay` can rebuild them byte for byte.
+//! Merkle leaves are unsalted unless the executor is given a key
+//! (`with_salt_key`); whoever keeps it can later disclose single steps of
+//! a run without the others being guessable.
+//!
+//! Wire privacy tiers map one-to-one onto `store::PrivacyTier`.
+//!
+//! An `Executor` is `Send + Sync`: factories and the clock are shared, and
+//! each run builds its own cartridge and engine.
+
+use crate::cartridge::{Cartridge, CartridgeOutput};
+use crate::constraints::Constraints;
+use crate::engine::PilgrimEngine;
+use crate::receipt::Receipt;
+use crate::store::PrivacyTier;
+use crate::trace::Trace;
+use amethyst_clock::{Clock, SystemClock};
+use amethyst_invariants::Enforced;
+use pilgrim_handshake::datum::DatumKind;
+use pilgrim_handshake::merkle::Hash;
+use pilgrim_handshake::{
+    cbor, Datum, Decimal, FieldSpec, HandshakeError, InputSchema, ReceiptPayload, RequestEnvelope,
+    ResponseEnvelope, RunPayload, RunResult, RunStatus, StepLog,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Input naming the cartridge to run.
+pub const CARTRIDGE_INPUT: &str = "cartridge";
+
+/// Input giving the number of ticks to drive.
+pub const TICKS_INPUT: &str = "ticks";
+
+/// First trace step: the intent's inputs, binding the run to them.
+pub const INPUTS_STEP: &str = "inputs";
+
+/// Builds a fresh cartridge for one run, from the intent's inputs.
+pub type CartridgeFactory = Box<dyn Fn(&[Datum]) -> Box<dyn Cartridge> + Send + Sync>;
+
+struct Registration {
+    inputs: Vec<FieldSpec>,
+    factory: CartridgeFactory,
+}
+
+/// Resolves cartridges and runs intents against them.
+pub struct Executor {
+    cartridges: BTreeMap<&'static str, Registration>,
+    clock: Arc<dyn Clock>,
+    salt_key: Option<Hash>,
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Self {
+            cartridges: BTreeMap::new(),
+            clock: Arc::new(SystemClock),
+            salt_key: None,
+        }
+    }
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Measure run time on `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Salt every run's Merkle leaves under `salt_key`. Keep it: proofs
+    /// for a run's steps can only be minted with it.
+    pub fn with_salt_key(mut self, salt_key: Hash) -> Self {
+        self.salt_key = Some(salt_key);
+        self
+    }
+
+    /// Register a cartridge under `id` (its `Cartridge::id`), with the
+    /// inputs it accepts beyond `cartridge` and `ticks`. `factory` is only
+    /// called to run an intent, never with made-up inputs.
+    pub fn register<F>(&mut self, id: &'static str, inputs: Vec<FieldSpec>, factory: F)
+    where
+        F: Fn(&[Datum]) -> Box<dyn Cartridge> + Send + Sync + 'static,
+    {
+        self.cartridges.insert(
+            id,
+            Registration {
+                inputs,
+                factory: Box::new(factory),
+            },
+        );
+    }
+
+    /// Registered cartridge ids, sorted.
+    pub fn cartridge_ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.cartridges.keys().copied()
+    }
+
+    /// Run `request` to completion.
+    ///
+    /// Integrity failures are errors, as are inputs, outputs and results
+    /// with no canonical form (`NumberOutOfRange`, `BadJson`). Everything else is a response sealed
+    /// like the request: `Rejected` (bad inputs, unknown cartridge),
+    /// `Failed` (halted by constraints) or `Completed`; runs that started
+    /// carry their payload (and logs, if required) either way.
+    pub fn execute(&self, request: &RequestEnvelope) -> Result<ResponseEnvelope, HandshakeError> {
+        request.verify()?;
+        let intent = &request.intent;
+        let algo = request.checksum.algo;
+
+        let requested = intent
+            .inputs
+            .iter()
+            .find(|d| d.key == CARTRIDGE_INPUT)
+            .and_then(|d| d.value.as_text());
+        let registration = match requested {
+            Some(id) => match self.cartridges.get(id) {
+                Some(registration) => Some(registration),
+                None => {
+                    return ResponseEnvelope::new_with_algo(
+                        intent.intent_id.clone(),
+                        RunStatus::Rejected,
+                        format!("Rejected: unknown cartridge `{}`.", id),
+                        algo,
+                    )
+                }
+            },
+            None => None,
+        };
+
+        let accepted = request.accept(&self.schema(registration, intent.constraints.max_steps))?;
+        let Some(registration) = registration.filter(|_| accepted.status == RunStatus::Accepted)
+        else {
+            return Ok(accepted);
+        };
+        let ticks = intent
+            .inputs
+            .iter()
+            .find(|d| d.key == TICKS_INPUT)
+            .and_then(|d| d.value.as_integer())
+            .expect("schema requires ticks") as u64;
+
+        let constraints = Constraints::from(&intent.constraints);
+        let require_logs = constraints.require_logs;
+        let mut cartridge = (registration.factory)(&intent.inputs);
+        let mut trace = Trace::new(&intent.intent_id, &intent.statement);
+        if let Some(salt_key) = self.salt_key {
+            trace = trace.with_salt_key(salt_key);
+        }
+        let mut engine = PilgrimEngine::new(constraints, self.clock.clone()).with_trace(trace);
+        let mut last: Option<CartridgeOutput> = None;
+        engine.record(INPUTS_STEP, &step_payload(&intent.inputs)?);
+
+        let halted = loop {
+            if engine.ticks() == ticks {
+                break engine.finish().err();
+            }
+            let tick = match engine.advance(cartridge.id()) {
+                Ok(tick) => tick,
+                Err(e) => break Some(e),
+            };
+            let output = cartridge.run(tick);
+            engine.record(&tick_step(cartridge.id(), tick), &step_payload(&output)?);
+            last = Some(output);
+        };
+        let trace = engine.take_trace().expect("trace attached above");
+
+        let receipt = Receipt::from_trace(&trace, engine.ticks());
+        let (status, message) = match &halted {
+            None => (RunStatus::Completed, "Completed.".to_string()),
+            Some(e) => (
+                RunStatus::Failed,
+                format!("Halted: {} ({}).", e, e.system_invariant()),
+            ),
+        };
+
+        let payload = RunPayload::new(
+            RunResult {
+                cartridge_id: cartridge.id().to_string(),
+                outputs: last.map(outputs).unwrap_or_default(),
+            },
+            ReceiptPayload {
+                run_id: receipt.run_id,
+                intent_statement: receipt.intent_statement,
+                final_trace_hash: receipt.final_trace_hash,
+                steps: receipt.steps,
+                merkle_root: receipt.merkle_root,
+                salted: receipt.salted,
+            },
+        );
+        let mut response =
+            ResponseEnvelope::new_with_algo(intent.intent_id.clone(), status, message, algo)?
+                .with_payload(payload)?;
+        if require_logs {
+            response = response.with_logs(step_logs(&trace))?;
+        }
+        Ok(response)
+    }
+
+    fn schema(&self, registration: Option<&Registration>, max_steps: u32) -> InputSchema {
+        let mut fields = vec![
+            FieldSpec::required(CARTRIDGE_INPUT, DatumKind::Text),
+            FieldSpec::required(TICKS_INPUT, DatumKind::Int).with_range(1, max_steps as i64),
+        ];
+        if let Some(registration) = registration {
+            fields.extend(registration.inputs.iter().cloned());
+        }
+        InputSchema::new(fields)
+    }
+}
+
+impl From<&pilgrim_handshake::Constraints> for Constraints {
+    fn from(c: &pilgrim_handshake::Constraints) -> Self {
+        use pilgrim_handshake::PrivacyTier as Wire;
+        Self {
+            max_steps: Some(c.max_steps as u64),
+            max_runtime_ms: Some(c.max_runtime_ms),
+            require_logs: c.require_logs,
+            privacy: match c.privacy {
+                Wire::Public => PrivacyTier::Public,
+                Wire::Protected => PrivacyTier::Protected,
+                Wire::Confidential => PrivacyTier::Confidential,
+                Wire::Sealed => PrivacyTier::Sealed,
+            },
+        }
+    }
+}
+
+/// Trace step name for one tick.
+pub(crate) fn tick_step(cartridge_id: &str, tick: u64) -> String {
+    format!("{}:{}", cartridge_id, tick)
+}
+
+/// Canonical CBOR of a step's payload. Covers the full `i64` range
+/// (unlike JCS, which stops at 2^53); anything else that has no canonical
+/// form is an error rather than an empty step.
+pub(crate) fn step_payload<T: Serialize>(value: &T) -> Result<Vec<u8>, HandshakeError> {
+    cbor::to_canonical_vec(value)
+}
+
+/// Final cartridge output as data: confidence as a 6-digit `Decimal`.
+fn outputs(output: CartridgeOutput) -> Vec<Datum> {
+    let confidence = Decimal {
+        units: (output.confidence as f64 * 1e6).round() as i64,
+        scale: 6,
+    };
+    vec![
+        Datum::new("message", output.message),
+        Datum::new("confidence", confidence),
+    ]
+}
+
+fn step_logs(trace: &Trace) -> Vec<StepLog> {
+    trace
+        .steps()
+        .iter()
+        .enumerate()
+        .map(|(index, step)| StepLog {
+            index: index as u64,
+            name: step.name.clone(),
+            checksum_hex: step.checksum_hex.clone(),
+            len: step.len as u64,
+        })
+        .collect()
+}
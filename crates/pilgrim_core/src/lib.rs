@@ -1,3 +1,15 @@
 pub mod cartridge;
+pub mod constraints;
+pub mod engine;
+pub mod executor;
+pub mod receipt;
+pub mod replay;
+pub mod store;
+pub mod trace;
 
 pub use cartridge::{Cartridge, CartridgeOutput};
+pub use engine::PilgrimEngine;
+pub use executor::Executor;
+pub use receipt::Receipt;
+pub use replay::{replay, ReplayError, Replayed};
+pub use trace::{StepProof, Trace, TraceStep};
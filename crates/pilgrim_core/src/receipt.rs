@@ -1,9 +1,25 @@
+use crate::trace::{StepProof, Trace, TraceStep};
+use pilgrim_handshake::merkle::Hash;
+use pilgrim_handshake::{ReceiptPayload, StepLog};
+
 #[derive(Debug, Clone)]
 pub struct Receipt {
     pub run_id: String,
     pub intent_statement: String,
     pub final_trace_hash: String,
     pub steps: u64,
+    /// Hex Merkle root over the trace steps; `StepProof`s verify against it
+    /// (empty if unknown).
+    pub merkle_root: String,
+    /// Per-step metadata behind `final_trace_hash`, in order (`None` if
+    /// the run's logs were not kept).
+    pub trace: Option<Vec<TraceStep>>,
+    /// Whether the Merkle leaves are salted.
+    pub salted: bool,
+    /// Key the Merkle leaf nonces derive from, if `salted`. The runner's
+    /// secret: never in `ReceiptPayload`, and needed to mint proofs or
+    /// re-derive a salted root.
+    pub salt_key: Option<Hash>,
 }
 
 impl Receipt {
@@ -13,6 +29,79 @@ impl Receipt {
             intent_statement: intent_statement.to_string(),
             final_trace_hash: final_trace_hash.to_string(),
             steps,
+            merkle_root: String::new(),
+            trace: None,
+            salted: false,
+            salt_key: None,
+        }
+    }
+
+    /// Receipt for a finished `trace` that ran `steps` ticks.
+    pub fn from_trace(trace: &Trace, steps: u64) -> Self {
+        Self {
+            run_id: trace.run_id().to_string(),
+            intent_statement: trace.intent_statement().to_string(),
+            final_trace_hash: trace.finalize_hash(),
+            steps,
+            merkle_root: trace.merkle_root(),
+            trace: Some(trace.steps().to_vec()),
+            salted: trace.salt_key().is_some(),
+            salt_key: trace.salt_key().copied(),
+        }
+    }
+
+    /// The runner's `salt_key`, for a salted receipt rebuilt from its
+    /// payload.
+    pub fn with_salt_key(mut self, salt_key: Hash) -> Self {
+        self.salt_key = Some(salt_key);
+        self
+    }
+
+    /// Inclusion proof for step `index` (needs the per-step metadata and,
+    /// if `salted`, the salt key).
+    pub fn inclusion_proof(&self, index: usize) -> Option<StepProof> {
+        StepProof::new(
+            &self.run_id,
+            self.trace.as_deref()?,
+            self.leaf_salt()?,
+            index,
+        )
+    }
+
+    /// How the Merkle leaves are salted, if known (`Some(None)`: unsalted).
+    pub(crate) fn leaf_salt(&self) -> Option<Option<&Hash>> {
+        match (self.salted, &self.salt_key) {
+            (false, _) => Some(None),
+            (true, Some(key)) => Some(Some(key)),
+            (true, None) => None,
+        }
+    }
+
+    /// Whether `proof` discloses a step of this receipt's trace.
+    pub fn verify_step(&self, proof: &StepProof) -> bool {
+        proof.verify(&self.merkle_root)
+    }
+
+    /// Rebuild a receipt from a response's payload and logs (if it carried
+    /// any), for replay by a third party.
+    pub fn from_payload(receipt: &ReceiptPayload, logs: Option<&[StepLog]>) -> Self {
+        Self {
+            run_id: receipt.run_id.clone(),
+            intent_statement: receipt.intent_statement.clone(),
+            final_trace_hash: receipt.final_trace_hash.clone(),
+            steps: receipt.steps,
+            merkle_root: receipt.merkle_root.clone(),
+            trace: logs.map(|logs| {
+                logs.iter()
+                    .map(|log| TraceStep {
+                        name: log.name.clone(),
+                        checksum_hex: log.checksum_hex.clone(),
+                        len: log.len as usize,
+                    })
+                    .collect()
+            }),
+            salted: receipt.salted,
+            salt_key: None,
         }
     }
 }
@@ -0,0 +1,196 @@
+//! Re-execution of a receipted run, step by step.
+//!
+//! `replay` drives a fresh cartridge through the steps the executor
+//! recorded (the `inputs` step, then one step per tick) and compares each
+//! against the receipt. Anyone holding the receipt, the inputs and the
+//! cartridge can check `final_trace_hash` without trusting whoever ran it.
+//!
+//! Constraints are not re-applied: the receipt says how many ticks ran,
+//! bounded by its own trace (or by the caller, for hash-only receipts).
+//! A salted Merkle root is only re-derived when the receipt carries the
+//! runner's salt key. A
+//! closing `halt` step depends on limits and wall time replay does not
+//! re-check, so it is carried over as recorded and reported as unverified.
+
+use crate::cartridge::Cartridge;
+use crate::executor::{step_payload, tick_step, INPUTS_STEP};
+use crate::receipt::Receipt;
+use crate::trace::{Trace, TraceStep, HALT_STEP};
+use amethyst_invariants::{Enforced, EnforcementPoint};
+use pilgrim_handshake::{Datum, HandshakeError};
+use std::fmt;
+
+// Same cartridge, same inputs: same steps
+const SAME_STEPS: EnforcementPoint =
+    EnforcementPoint::new("DET_001", "pilgrim_core::replay::replay");
+// The receipt's hash and root must follow from those steps
+const DERIVED: EnforcementPoint =
+    EnforcementPoint::new("TRUTH_001", "pilgrim_core::replay::replay");
+
+/// System invariants checked by replay.
+pub const ENFORCEMENT: &[EnforcementPoint] = &[SAME_STEPS, DERIVED];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// First step that differs; `None` where one side has no step there.
+    Diverged {
+        index: usize,
+        recorded: Option<TraceStep>,
+        replayed: Option<TraceStep>,
+    },
+    /// Every step matches, but the receipt's hash does not.
+    HashMismatch { recorded: String, replayed: String },
+    /// Every step matches, but the receipt's Merkle root does not.
+    RootMismatch { recorded: String, replayed: String },
+    /// A replayed step's payload has no canonical form.
+    Payload(HandshakeError),
+    /// The receipt claims more ticks than its trace holds, or than the
+    /// caller allows.
+    TooManySteps { steps: u64, max: u64 },
+}
+
+/// A receipt that replay reproduced.
+#[derive(Debug, Clone)]
+pub struct Replayed {
+    /// The re-derived trace, ending in the recorded `halt` step if any.
+    pub trace: Trace,
+    /// The receipt's `halt` step, if it has one. Only its place in the
+    /// hash was checked, not why the run halted.
+    pub unverified_halt: Option<TraceStep>,
+}
+
+impl Enforced for ReplayError {
+    fn enforcement_point(&self) -> &'static EnforcementPoint {
+        match self {
+            ReplayError::Diverged { .. } | ReplayError::Payload(_) => &SAME_STEPS,
+            ReplayError::HashMismatch { .. }
+            | ReplayError::RootMismatch { .. }
+            | ReplayError::TooManySteps { .. } => &DERIVED,
+        }
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn side(step: &Option<TraceStep>) -> String {
+            match step {
+                Some(s) => format!("{} {}", s.name, s.checksum_hex),
+                None => "(none)".to_string(),
+            }
+        }
+        match self {
+            ReplayError::Diverged {
+                index,
+                recorded,
+                replayed,
+            } => write!(
+                f,
+                "step {} diverged: recorded {}, replayed {}",
+                index,
+                side(recorded),
+                side(replayed)
+            ),
+            ReplayError::HashMismatch { recorded, replayed } => write!(
+                f,
+                "trace hash mismatch: recorded {}, replayed {}",
+                recorded, replayed
+            ),
+            ReplayError::RootMismatch { recorded, replayed } => write!(
+                f,
+                "merkle root mismatch: recorded {}, replayed {}",
+                recorded, replayed
+            ),
+            ReplayError::Payload(e) => write!(f, "step payload has no canonical form: {:?}", e),
+            ReplayError::TooManySteps { steps, max } => {
+                write!(f, "receipt claims {} steps, at most {}", steps, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Re-run `cartridge` on `inputs` for `receipt.steps` ticks and check the
+/// result against `receipt`.
+///
+/// `cartridge` must be fresh, built from `inputs` as for the original run.
+/// Steps are compared one by one when the receipt carries them; otherwise
+/// only the hashes are (and a halted run cannot match, its `halt` step
+/// being unknown). `final_trace_hash` is always checked, `merkle_root` if
+/// set (and, for salted leaves, `salt_key` is known).
+///
+/// `receipt.steps` is not trusted: it may not exceed the receipt's trace
+/// length or, for hash-only receipts, `max_ticks`.
+pub fn replay(
+    receipt: &Receipt,
+    cartridge: &mut dyn Cartridge,
+    inputs: &[Datum],
+    max_ticks: u64,
+) -> Result<Replayed, ReplayError> {
+    let recorded = receipt.trace.as_deref();
+    let max = recorded.map_or(max_ticks, |steps| steps.len() as u64);
+    if receipt.steps > max {
+        return Err(ReplayError::TooManySteps {
+            steps: receipt.steps,
+            max,
+        });
+    }
+
+    let leaf_salt = receipt.leaf_salt();
+    let mut trace = Trace::new(&receipt.run_id, &receipt.intent_statement);
+    if let Some(Some(salt_key)) = leaf_salt {
+        trace = trace.with_salt_key(*salt_key);
+    }
+    trace.push_step(
+        INPUTS_STEP,
+        &step_payload(&inputs).map_err(ReplayError::Payload)?,
+    );
+    for tick in 0..receipt.steps {
+        let output = cartridge.run(tick);
+        let payload = step_payload(&output).map_err(ReplayError::Payload)?;
+        trace.push_step(&tick_step(cartridge.id(), tick), &payload);
+    }
+
+    let ran = trace.steps_len();
+    let unverified_halt = recorded
+        .and_then(|steps| steps.get(ran).filter(|_| steps.len() == ran + 1))
+        .filter(|step| step.name == HALT_STEP)
+        .cloned();
+    if let Some(halt) = &unverified_halt {
+        trace.push_recorded(halt.clone());
+    }
+
+    if let Some(recorded) = recorded {
+        let replayed = trace.steps();
+        let diverged =
+            (0..recorded.len().max(replayed.len())).find(|&i| recorded.get(i) != replayed.get(i));
+        if let Some(index) = diverged {
+            return Err(ReplayError::Diverged {
+                index,
+                recorded: recorded.get(index).cloned(),
+                replayed: replayed.get(index).cloned(),
+            });
+        }
+    }
+
+    let hash = trace.finalize_hash();
+    if hash != receipt.final_trace_hash {
+        return Err(ReplayError::HashMismatch {
+            recorded: receipt.final_trace_hash.clone(),
+            replayed: hash,
+        });
+    }
+    if leaf_salt.is_some() && !receipt.merkle_root.is_empty() {
+        let root = trace.merkle_root();
+        if root != receipt.merkle_root {
+            return Err(ReplayError::RootMismatch {
+                recorded: receipt.merkle_root.clone(),
+                replayed: root,
+            });
+        }
+    }
+    Ok(Replayed {
+        trace,
+        unverified_halt,
+    })
+}
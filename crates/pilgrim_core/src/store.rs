@@ -1,6 +1,7 @@
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PrivacyTier {
     Public,
-    Private,
+    Protected,
+    Confidential,
     Sealed,
 }
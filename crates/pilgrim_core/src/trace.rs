@@ -1,28 +1,135 @@
+use crate::constraints::ConstraintsError;
+use pilgrim_handshake::merkle::{self, Hash, MerkleTree};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+/// Name of the step recording why a run halted.
+pub const HALT_STEP: &str = "halt";
+
 #[derive(Debug, Clone)]
 pub struct Trace {
     run_id: String,
     intent_statement: String,
     steps: Vec<TraceStep>,
+    /// Secret the Merkle leaf nonces derive from, if the leaves are salted
+    /// (not hashed into `finalize_hash`).
+    salt_key: Option<Hash>,
+    halt: Option<ConstraintsError>,
+    /// Clock readings taken during the run (not hashed: wall time differs
+    /// between otherwise identical runs).
+    clock_readings: Vec<u64>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TraceStep {
     pub name: String,
     pub checksum_hex: String,
     pub len: usize,
 }
 
+impl TraceStep {
+    /// Merkle leaf: the step's metadata (`name:checksum:len`, prefixed
+    /// with `nonce:` when salted), never its payload. The nonce keeps
+    /// undisclosed leaves from being guessed from their sibling hashes.
+    pub fn leaf_hash(&self, nonce: Option<&Hash>) -> Hash {
+        let meta = format!("{}:{}:{}", self.name, self.checksum_hex, self.len);
+        match nonce {
+            Some(nonce) => merkle::leaf_hash(format!("{}:{}", hex::encode(nonce), meta).as_bytes()),
+            None => merkle::leaf_hash(meta.as_bytes()),
+        }
+    }
+}
+
+/// One step, disclosed with its path to the trace's Merkle root.
+///
+/// The path holds sibling hashes only, so the other steps (sealed ones
+/// included) stay undisclosed. In a salted trace each leaf also has its
+/// own nonce, so they cannot be guessed from those hashes either.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepProof {
+    pub index: u64,
+    /// Number of steps in the trace.
+    pub size: u64,
+    pub step: TraceStep,
+    /// Hex nonce salting this step's leaf (and no other); `None` for an
+    /// unsalted trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// Hex sibling hashes, leaf to root.
+    pub path: Vec<String>,
+}
+
+impl StepProof {
+    /// Proof for `steps[index]` within the steps of run `run_id`, salted
+    /// under `salt_key` if given.
+    pub fn new(
+        run_id: &str,
+        steps: &[TraceStep],
+        salt_key: Option<&Hash>,
+        index: usize,
+    ) -> Option<Self> {
+        let path = step_tree(run_id, steps, salt_key).proof(index)?;
+        Some(Self {
+            index: index as u64,
+            size: steps.len() as u64,
+            step: steps[index].clone(),
+            nonce: salt_key.map(|key| hex::encode(step_nonce(key, run_id, index))),
+            path: path.iter().map(hex::encode).collect(),
+        })
+    }
+
+    /// Whether `step` is step `index` of the trace with hex root `root`.
+    pub fn verify(&self, root: &str) -> bool {
+        let (Some(root), Some(nonce), Some(path)) = (
+            decode_hash(root),
+            match &self.nonce {
+                Some(nonce) => decode_hash(nonce).map(Some),
+                None => Some(None),
+            },
+            self.path
+                .iter()
+                .map(|h| decode_hash(h))
+                .collect::<Option<Vec<_>>>(),
+        ) else {
+            return false;
+        };
+        merkle::verify_proof(
+            self.index,
+            self.size,
+            &self.step.leaf_hash(nonce.as_ref()),
+            &path,
+            &root,
+        )
+    }
+
+    /// `verify`, and `payload` is the disclosed step's content.
+    pub fn verify_payload(&self, payload: &[u8], root: &str) -> bool {
+        payload.len() == self.step.len
+            && sha256_hex(payload) == self.step.checksum_hex
+            && self.verify(root)
+    }
+}
+
 impl Trace {
+    /// New trace with unsalted Merkle leaves (see `with_salt_key`).
     pub fn new(run_id: &str, intent_statement: &str) -> Self {
         Self {
             run_id: run_id.to_string(),
             intent_statement: intent_statement.to_string(),
             steps: Vec::new(),
+            salt_key: None,
+            halt: None,
+            clock_readings: Vec::new(),
         }
     }
 
+    /// Salt the Merkle leaves under `salt_key`. The same key re-derives
+    /// the same root, and mints proofs for a trace recorded earlier.
+    pub fn with_salt_key(mut self, salt_key: Hash) -> Self {
+        self.salt_key = Some(salt_key);
+        self
+    }
+
     pub fn push_step(&mut self, name: &str, payload: &[u8]) {
         let checksum_hex = sha256_hex(payload);
         self.steps.push(TraceStep {
@@ -32,10 +139,61 @@ impl Trace {
         });
     }
 
+    /// Append a step as recorded elsewhere (its payload is not at hand).
+    pub fn push_recorded(&mut self, step: TraceStep) {
+        self.steps.push(step);
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    pub fn intent_statement(&self) -> &str {
+        &self.intent_statement
+    }
+
     pub fn steps_len(&self) -> usize {
         self.steps.len()
     }
 
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// Keep this to mint `StepProof`s later; never publish it.
+    pub fn salt_key(&self) -> Option<&Hash> {
+        self.salt_key.as_ref()
+    }
+
+    /// Record why the run stopped, as a final `halt` step.
+    pub fn record_halt(&mut self, error: ConstraintsError) {
+        self.push_step(HALT_STEP, error.to_string().as_bytes());
+        self.halt = Some(error);
+    }
+
+    pub fn halt(&self) -> Option<&ConstraintsError> {
+        self.halt.as_ref()
+    }
+
+    pub fn record_clock(&mut self, now_ms: u64) {
+        self.clock_readings.push(now_ms);
+    }
+
+    /// Readings to feed a `ReplayClock` for an exact re-run.
+    pub fn clock_readings(&self) -> &[u64] {
+        &self.clock_readings
+    }
+
+    /// Hex Merkle root over the steps, in order.
+    pub fn merkle_root(&self) -> String {
+        hex::encode(step_tree(&self.run_id, &self.steps, self.salt_key.as_ref()).root())
+    }
+
+    /// Inclusion proof for step `index`, against `merkle_root`.
+    pub fn inclusion_proof(&self, index: usize) -> Option<StepProof> {
+        StepProof::new(&self.run_id, &self.steps, self.salt_key.as_ref(), index)
+    }
+
     pub fn finalize_hash(&self) -> String {
         // Deterministic hash of: run_id, intent_statement, and step metadata in order.
         let mut hasher = Sha256::new();
@@ -62,3 +220,29 @@ fn sha256_hex(data: &[u8]) -> String {
     h.update(data);
     hex::encode(h.finalize())
 }
+
+/// Nonce for step `index` of run `run_id`: only its own leaf's salt.
+fn step_nonce(salt_key: &Hash, run_id: &str, index: usize) -> Hash {
+    let mut h = Sha256::new();
+    h.update(salt_key);
+    h.update(run_id.as_bytes());
+    h.update(b"\n");
+    h.update((index as u64).to_be_bytes());
+    h.finalize().into()
+}
+
+fn step_tree(run_id: &str, steps: &[TraceStep], salt_key: Option<&Hash>) -> MerkleTree {
+    MerkleTree::from_leaves(
+        steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                step.leaf_hash(salt_key.map(|key| step_nonce(key, run_id, i)).as_ref())
+            })
+            .collect(),
+    )
+}
+
+fn decode_hash(hex_str: &str) -> Option<Hash> {
+    hex::decode(hex_str).ok()?.try_into().ok()
+}
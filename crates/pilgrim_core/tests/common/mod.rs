@@ -0,0 +1,73 @@
+//! Cartridge and fixtures shared by the executor-level tests.
+//!
+//! Each test binary compiles its own copy and uses only some of it.
+#![allow(dead_code)]
+
+use amethyst_clock::StepClock;
+use pilgrim_core::executor::{CARTRIDGE_INPUT, TICKS_INPUT};
+use pilgrim_core::{Cartridge, CartridgeOutput, Executor};
+use pilgrim_handshake::datum::DatumKind;
+use pilgrim_handshake::*;
+use std::sync::Arc;
+
+/// The runner's key for salted Merkle leaves.
+pub const SALT_KEY: [u8; 32] = [7; 32];
+
+/// Counts up from 0, one `stride` per tick.
+pub struct Counter {
+    pub stride: i64,
+}
+
+impl Cartridge for Counter {
+    fn id(&self) -> &'static str {
+        "counter_v1"
+    }
+
+    fn run(&mut self, tick: u64) -> CartridgeOutput {
+        CartridgeOutput {
+            message: format!("count {}", self.stride * tick as i64),
+            confidence: 0.75,
+        }
+    }
+}
+
+/// `counter_v1` registered (optional `stride`, 1..=10, default 1), on a
+/// clock that moves 10 ms per read.
+pub fn executor() -> Executor {
+    let mut ex = Executor::new().with_clock(Arc::new(StepClock::new(1700000000000, 10)));
+    ex.register(
+        "counter_v1",
+        vec![FieldSpec::optional("stride", DatumKind::Int).with_range(1, 10)],
+        |inputs| {
+            let stride = inputs
+                .iter()
+                .find(|d| d.key == "stride")
+                .and_then(|d| d.value.as_integer())
+                .unwrap_or(1);
+            Box::new(Counter { stride })
+        },
+    );
+    ex
+}
+
+/// Inputs for `ticks` ticks of `counter_v1`.
+pub fn counter_inputs(ticks: i64, stride: i64) -> Vec<Datum> {
+    vec![
+        Datum::new(CARTRIDGE_INPUT, "counter_v1"),
+        Datum::new(TICKS_INPUT, ticks),
+        Datum::new("stride", stride),
+    ]
+}
+
+pub fn request(intent_id: &str, inputs: Vec<Datum>, constraints: Constraints) -> RequestEnvelope {
+    RequestEnvelope::new(Intent {
+        intent_id: intent_id.to_string(),
+        created_unix_ms: 1700000000000,
+        operator: None,
+        statement: "Count.".to_string(),
+        inputs,
+        constraints,
+        nonce: 1,
+    })
+    .unwrap()
+}
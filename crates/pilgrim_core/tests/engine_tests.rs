@@ -1,7 +1,125 @@
+use amethyst_clock::{RecordedClock, ReplayClock, StepClock, SystemClock};
+use amethyst_invariants::Enforced;
+use pilgrim_core::constraints::{Constraints, ConstraintsError};
+use pilgrim_core::trace::Trace;
 use pilgrim_core::PilgrimEngine;
+use std::sync::Arc;
 
 #[test]
 fn engine_advances_deterministically() {
     let mut engine = PilgrimEngine::default();
-    engine.step("engine-test");
+    engine.step("engine-test").unwrap();
+    assert_eq!(engine.ticks(), 1);
+}
+
+fn bounded(max_steps: Option<u64>, max_runtime_ms: Option<u64>, require_logs: bool) -> Constraints {
+    Constraints {
+        max_steps,
+        max_runtime_ms,
+        require_logs,
+        ..Constraints::default()
+    }
+}
+
+#[test]
+fn step_limit_halts_and_is_traced() {
+    let mut engine =
+        PilgrimEngine::new(bounded(Some(2), None, false), Arc::new(StepClock::fixed(0)))
+            .with_trace(Trace::new("run-1", "Two steps."));
+
+    assert_eq!(engine.advance("a"), Ok(0));
+    assert_eq!(engine.advance("b"), Ok(1));
+    let limit = ConstraintsError::StepLimitExceeded {
+        max_steps: 2,
+        attempted_step_index: 2,
+    };
+    assert_eq!(engine.advance("c"), Err(limit.clone()));
+    assert_eq!(engine.ticks(), 2);
+
+    // Halted for good
+    assert_eq!(engine.advance("d"), Err(limit.clone()));
+    assert_eq!(engine.finish(), Err(limit.clone()));
+
+    let trace = engine.trace().unwrap();
+    assert_eq!(trace.halt(), Some(&limit));
+    assert_eq!(trace.steps().last().unwrap().name, "halt");
+    assert_eq!(trace.steps_len(), 1);
+}
+
+#[test]
+fn runtime_is_measured_on_the_injected_clock() {
+    let clock = Arc::new(StepClock::new(1_000, 40));
+    let mut engine = PilgrimEngine::new(bounded(None, Some(100), false), clock);
+
+    // Reads: 1000 (start), 1040, 1080, 1120
+    for expected in 0..3 {
+        assert_eq!(engine.advance("tick"), Ok(expected));
+    }
+    assert_eq!(
+        engine.advance("tick"),
+        Err(ConstraintsError::RuntimeLimitExceeded {
+            max_runtime_ms: 100,
+            elapsed_ms: 120,
+        })
+    );
+    assert_eq!(engine.halted().unwrap().system_invariant(), "SAFE_001");
+}
+
+#[test]
+fn finish_catches_time_spent_in_the_last_tick() {
+    let mut engine = PilgrimEngine::new(
+        bounded(None, Some(50), false),
+        Arc::new(StepClock::new(0, 60)),
+    );
+    engine.advance("only").unwrap();
+    assert!(matches!(
+        engine.finish(),
+        Err(ConstraintsError::RuntimeLimitExceeded { elapsed_ms: 60, .. })
+    ));
+}
+
+#[test]
+fn required_logs_need_a_trace() {
+    let mut engine = PilgrimEngine::new(bounded(None, None, true), Arc::new(StepClock::fixed(0)));
+    assert_eq!(engine.advance("a"), Err(ConstraintsError::TraceRequired));
+    assert_eq!(engine.ticks(), 0);
+    assert_eq!(
+        ConstraintsError::TraceRequired.system_invariant(),
+        "TRANS_001"
+    );
+
+    let mut engine = PilgrimEngine::new(bounded(None, None, true), Arc::new(StepClock::fixed(0)))
+        .with_trace(Trace::new("run-2", "Logged."));
+    engine.advance("a").unwrap();
+    engine.record("a", b"payload");
+    assert_eq!(engine.take_trace().unwrap().steps_len(), 1);
+}
+
+#[test]
+fn engine_enforcement_maps_to_system_invariants() {
+    use amethyst_invariants::CoverageReport;
+
+    let report = CoverageReport::new(&[pilgrim_core::engine::ENFORCEMENT]);
+    assert_eq!(report.unknown().count(), 0);
+    assert!(report.enforced().any(|inv| inv.id == "TRANS_001"));
+}
+
+#[test]
+fn recorded_timings_replay_exactly() {
+    let run = |clock: Arc<dyn amethyst_clock::Clock>| {
+        let mut engine = PilgrimEngine::new(bounded(Some(3), Some(60_000), true), clock)
+            .with_trace(Trace::new("run-3", "Timed."));
+        while engine.advance("tick").is_ok() {}
+        engine.take_trace().unwrap()
+    };
+
+    let live = run(Arc::new(RecordedClock::new(SystemClock)));
+    assert_eq!(live.clock_readings().len(), 4);
+
+    let replay = Arc::new(ReplayClock::new(live.clock_readings().to_vec()));
+    let again = run(replay.clone());
+    assert_eq!(again.clock_readings(), live.clock_readings());
+    assert_eq!(again.halt(), live.halt());
+    assert_eq!(again.finalize_hash(), live.finalize_hash());
+    assert!(!replay.overrun());
 }
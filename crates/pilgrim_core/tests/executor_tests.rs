@@ -0,0 +1,190 @@
+mod common;
+
+use common::{counter_inputs, executor};
+use pilgrim_core::executor::{CARTRIDGE_INPUT, INPUTS_STEP, TICKS_INPUT};
+use pilgrim_handshake::*;
+
+fn request(inputs: Vec<Datum>, constraints: Constraints) -> RequestEnvelope {
+    common::request("intent-exec", inputs, constraints)
+}
+
+fn counter_run(ticks: i64) -> Vec<Datum> {
+    counter_inputs(ticks, 2)
+}
+
+#[test]
+fn intent_runs_end_to_end() {
+    let ex = executor();
+    assert_eq!(ex.cartridge_ids().collect::<Vec<_>>(), vec!["counter_v1"]);
+
+    let resp = ex
+        .execute(&request(counter_run(3), Constraints::default()))
+        .unwrap();
+    resp.verify().unwrap();
+    assert_eq!(resp.status, RunStatus::Completed);
+
+    let payload = resp.payload.clone().unwrap();
+    assert_eq!(payload.result.cartridge_id, "counter_v1");
+    assert_eq!(
+        payload.result.outputs,
+        vec![
+            Datum::new("message", "count 4"),
+            Datum::new(
+                "confidence",
+                Decimal {
+                    units: 750_000,
+                    scale: 6
+                }
+            ),
+        ]
+    );
+    assert_eq!(payload.step_count(), 3);
+    assert_eq!(payload.receipt.run_id, "intent-exec");
+
+    let logs = resp.logs.clone().unwrap();
+    let names: Vec<_> = logs.iter().map(|l| l.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![INPUTS_STEP, "counter_v1:0", "counter_v1:1", "counter_v1:2"]
+    );
+
+    // Same intent, same trace
+    let again = ex
+        .execute(&request(counter_run(3), Constraints::default()))
+        .unwrap();
+    assert_eq!(again.payload.unwrap().trace_hash(), payload.trace_hash());
+}
+
+#[test]
+fn logs_are_attached_only_when_required() {
+    let constraints = Constraints {
+        require_logs: false,
+        ..Constraints::default()
+    };
+    let resp = executor()
+        .execute(&request(counter_run(2), constraints))
+        .unwrap();
+    assert_eq!(resp.status, RunStatus::Completed);
+    assert_eq!(resp.logs, None);
+}
+
+#[test]
+fn bad_intents_are_rejected_before_any_tick() {
+    let ex = executor();
+    let constraints = Constraints {
+        max_steps: 5,
+        ..Constraints::default()
+    };
+
+    let resp = ex
+        .execute(&request(
+            vec![
+                Datum::new(CARTRIDGE_INPUT, "counter_v1"),
+                Datum::new(TICKS_INPUT, 6i64),
+                Datum::new("strdie", 2i64),
+            ],
+            constraints.clone(),
+        ))
+        .unwrap();
+    resp.verify().unwrap();
+    assert_eq!(resp.status, RunStatus::Rejected);
+    assert_eq!(resp.payload, None);
+    let problems: Vec<_> = resp
+        .violations
+        .unwrap()
+        .into_iter()
+        .map(|v| (v.key, v.problem))
+        .collect();
+    assert_eq!(
+        problems,
+        vec![
+            (
+                TICKS_INPUT.to_string(),
+                InputProblem::OutOfRange { min: 1, max: 5 }
+            ),
+            ("strdie".to_string(), InputProblem::Unknown),
+        ]
+    );
+
+    // Nothing names the cartridge
+    let resp = ex
+        .execute(&request(
+            vec![Datum::new(TICKS_INPUT, 1i64)],
+            constraints.clone(),
+        ))
+        .unwrap();
+    assert_eq!(resp.status, RunStatus::Rejected);
+    assert_eq!(resp.violations.unwrap()[0].problem, InputProblem::Missing);
+
+    let resp = ex
+        .execute(&request(
+            vec![
+                Datum::new(CARTRIDGE_INPUT, "counter_v2"),
+                Datum::new(TICKS_INPUT, 1i64),
+            ],
+            constraints,
+        ))
+        .unwrap();
+    assert_eq!(resp.status, RunStatus::Rejected);
+    assert!(resp.message.contains("counter_v2"));
+}
+
+#[test]
+fn tampered_requests_are_errors() {
+    let mut req = request(counter_run(1), Constraints::default());
+    req.intent.statement.push('!');
+    assert_eq!(
+        executor().execute(&req).err(),
+        Some(HandshakeError::ChecksumMismatch)
+    );
+}
+
+#[test]
+fn runtime_limit_halts_the_run() {
+    let constraints = Constraints {
+        max_runtime_ms: 25,
+        ..Constraints::default()
+    };
+    // Every clock read is 10 ms later: ticks start at 0, 10, 20; 30 is over
+    let resp = executor()
+        .execute(&request(counter_run(10), constraints))
+        .unwrap();
+    resp.verify().unwrap();
+    assert_eq!(resp.status, RunStatus::Failed);
+    assert!(resp.message.contains("SAFE_001"));
+
+    // The partial run is still receipted, halt included
+    assert_eq!(resp.payload.unwrap().step_count(), 3);
+    let logs = resp.logs.unwrap();
+    assert_eq!(logs.len(), 5);
+    assert_eq!(logs[4].name, "halt");
+}
+
+#[test]
+fn executors_can_be_shared_across_threads() {
+    let ex = executor();
+    let req = request(counter_run(3), Constraints::default());
+    let resp = std::thread::scope(|s| s.spawn(|| ex.execute(&req)).join().unwrap()).unwrap();
+    assert_eq!(resp.status, RunStatus::Completed);
+}
+
+#[test]
+fn privacy_tiers_map_one_to_one() {
+    use pilgrim_core::store::PrivacyTier as Core;
+    let cases = [
+        (PrivacyTier::Public, Core::Public),
+        (PrivacyTier::Protected, Core::Protected),
+        (PrivacyTier::Confidential, Core::Confidential),
+        (PrivacyTier::Sealed, Core::Sealed),
+    ];
+    for (wire, core) in cases {
+        let constraints = Constraints {
+            privacy: wire,
+            ..Constraints::default()
+        };
+        assert_eq!(
+            pilgrim_core::constraints::Constraints::from(&constraints).privacy,
+            core
+        );
+    }
+}
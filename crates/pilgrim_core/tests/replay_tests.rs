@@ -0,0 +1,265 @@
+mod common;
+
+use amethyst_invariants::{CoverageReport, Enforced};
+use common::{counter_inputs, executor, request, Counter, SALT_KEY};
+use pilgrim_core::{replay, Executor, Receipt, ReplayError};
+use pilgrim_handshake::*;
+
+/// Most ticks replayed for a receipt without a trace.
+const MAX_TICKS: u64 = 100;
+
+/// Run `inputs` through the executor; the receipt as a third party sees it.
+fn receipted(inputs: Vec<Datum>, constraints: Constraints) -> Receipt {
+    receipted_by(&executor().with_salt_key(SALT_KEY), inputs, constraints)
+}
+
+fn receipted_by(ex: &Executor, inputs: Vec<Datum>, constraints: Constraints) -> Receipt {
+    let resp = ex
+        .execute(&request("intent-replay", inputs, constraints))
+        .unwrap();
+    resp.verify().unwrap();
+    let payload = resp.payload.unwrap();
+    Receipt::from_payload(&payload.receipt, resp.logs.as_deref())
+}
+
+#[test]
+fn faithful_replay_matches_the_receipt() {
+    let receipt = receipted(counter_inputs(4, 3), Constraints::default());
+    let replayed = replay(
+        &receipt,
+        &mut Counter { stride: 3 },
+        &counter_inputs(4, 3),
+        MAX_TICKS,
+    )
+    .unwrap();
+    assert_eq!(replayed.trace.finalize_hash(), receipt.final_trace_hash);
+    assert_eq!(replayed.trace.steps(), receipt.trace.as_deref().unwrap());
+    assert_eq!(replayed.unverified_halt, None);
+
+    // With the runner's key, the Merkle root is re-derived too
+    let receipt = receipt.with_salt_key(SALT_KEY);
+    let replayed = replay(
+        &receipt,
+        &mut Counter { stride: 3 },
+        &counter_inputs(4, 3),
+        MAX_TICKS,
+    )
+    .unwrap();
+    assert_eq!(replayed.trace.merkle_root(), receipt.merkle_root);
+}
+
+#[test]
+fn first_divergent_step_is_reported() {
+    let receipt = receipted(counter_inputs(4, 3), Constraints::default());
+
+    // Same inputs, different behaviour: tick 0 agrees (0 * stride), tick 1 not
+    let err = replay(
+        &receipt,
+        &mut Counter { stride: 2 },
+        &counter_inputs(4, 3),
+        MAX_TICKS,
+    )
+    .unwrap_err();
+    let ReplayError::Diverged {
+        index,
+        recorded,
+        replayed,
+    } = &err
+    else {
+        panic!("expected divergence, got {:?}", err);
+    };
+    assert_eq!(*index, 2);
+    let (recorded, replayed) = (recorded.clone().unwrap(), replayed.clone().unwrap());
+    assert_eq!(recorded.name, "counter_v1:1");
+    assert_eq!(replayed.name, "counter_v1:1");
+    assert_ne!(recorded.checksum_hex, replayed.checksum_hex);
+    assert!(err.to_string().contains(&recorded.checksum_hex));
+    assert!(err.to_string().contains(&replayed.checksum_hex));
+
+    // Different inputs diverge at the very first step
+    let err = replay(
+        &receipt,
+        &mut Counter { stride: 3 },
+        &counter_inputs(4, 2),
+        MAX_TICKS,
+    )
+    .unwrap_err();
+    assert!(matches!(err, ReplayError::Diverged { index: 0, .. }));
+    assert_eq!(err.system_invariant(), "DET_001");
+}
+
+#[test]
+fn tampered_receipts_fail() {
+    let mut receipt = receipted(counter_inputs(2, 1), Constraints::default());
+    receipt.final_trace_hash = "00".repeat(32);
+    let err = replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(2, 1),
+        MAX_TICKS,
+    )
+    .unwrap_err();
+    assert!(matches!(err, ReplayError::HashMismatch { .. }));
+    assert_eq!(err.system_invariant(), "TRUTH_001");
+
+    let mut receipt = receipted(counter_inputs(2, 1), Constraints::default());
+    receipt.merkle_root = "00".repeat(32);
+    // Salted with a key replay does not have: unchecked (an unsalted
+    // root is always checked, see `unsalted_roots_are_always_checked`)
+    replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(2, 1),
+        MAX_TICKS,
+    )
+    .unwrap();
+    let receipt = receipt.with_salt_key(SALT_KEY);
+    let err = replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(2, 1),
+        MAX_TICKS,
+    )
+    .unwrap_err();
+    assert!(matches!(err, ReplayError::RootMismatch { .. }));
+
+    // A dropped step shows up where it went missing
+    let mut receipt = receipted(counter_inputs(2, 1), Constraints::default());
+    receipt.trace.as_mut().unwrap().pop();
+    let err = replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(2, 1),
+        MAX_TICKS,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        ReplayError::Diverged {
+            index: 2,
+            recorded: None,
+            replayed: Some(_)
+        }
+    ));
+}
+
+#[test]
+fn halted_runs_replay_up_to_the_halt() {
+    let constraints = Constraints {
+        max_runtime_ms: 25,
+        ..Constraints::default()
+    };
+    let receipt = receipted(counter_inputs(10, 1), constraints);
+    assert_eq!(receipt.steps, 3);
+    assert_eq!(receipt.trace.as_ref().unwrap().last().unwrap().name, "halt");
+
+    let replayed = replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(10, 1),
+        MAX_TICKS,
+    )
+    .unwrap();
+    assert_eq!(replayed.trace.finalize_hash(), receipt.final_trace_hash);
+
+    // Why it halted is taken on trust, and said so
+    assert_eq!(
+        replayed.unverified_halt.as_ref(),
+        receipt.trace.as_ref().unwrap().last()
+    );
+}
+
+#[test]
+fn receipts_cannot_claim_unbounded_steps() {
+    let mut receipt = receipted(counter_inputs(2, 1), Constraints::default());
+    receipt.steps = u64::MAX;
+    let err = replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(2, 1),
+        MAX_TICKS,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ReplayError::TooManySteps {
+            steps: u64::MAX,
+            max: 3
+        }
+    );
+    assert_eq!(err.system_invariant(), "TRUTH_001");
+
+    // Without a trace, the caller's bound applies
+    receipt.trace = None;
+    assert_eq!(
+        replay(
+            &receipt,
+            &mut Counter { stride: 1 },
+            &counter_inputs(2, 1),
+            MAX_TICKS
+        )
+        .unwrap_err(),
+        ReplayError::TooManySteps {
+            steps: u64::MAX,
+            max: MAX_TICKS
+        }
+    );
+}
+
+#[test]
+fn hash_only_receipts_are_checked_on_the_hash() {
+    let constraints = Constraints {
+        require_logs: false,
+        ..Constraints::default()
+    };
+    let receipt = receipted(counter_inputs(3, 1), constraints);
+    assert_eq!(receipt.trace, None);
+    replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(3, 1),
+        MAX_TICKS,
+    )
+    .unwrap();
+    assert!(matches!(
+        replay(
+            &receipt,
+            &mut Counter { stride: 2 },
+            &counter_inputs(3, 1),
+            MAX_TICKS
+        ),
+        Err(ReplayError::HashMismatch { .. })
+    ));
+}
+
+#[test]
+fn replay_enforcement_is_declared() {
+    let report = CoverageReport::new(&[pilgrim_core::replay::ENFORCEMENT]);
+    assert_eq!(report.unknown().count(), 0);
+    assert!(report.enforced().any(|inv| inv.id == "DET_001"));
+}
+
+#[test]
+fn unsalted_roots_are_always_checked() {
+    let ex = executor();
+    let mut receipt = receipted_by(&ex, counter_inputs(2, 1), Constraints::default());
+    assert!(!receipt.salted);
+    replay(
+        &receipt,
+        &mut Counter { stride: 1 },
+        &counter_inputs(2, 1),
+        MAX_TICKS,
+    )
+    .unwrap();
+
+    receipt.merkle_root = "00".repeat(32);
+    assert!(matches!(
+        replay(
+            &receipt,
+            &mut Counter { stride: 1 },
+            &counter_inputs(2, 1),
+            MAX_TICKS
+        ),
+        Err(ReplayError::RootMismatch { .. })
+    ));
+}
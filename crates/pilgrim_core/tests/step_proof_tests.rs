@@ -0,0 +1,148 @@
+mod common;
+
+use common::{counter_inputs, executor, request, SALT_KEY};
+use pilgrim_core::{Receipt, StepProof, Trace};
+use pilgrim_handshake::*;
+
+fn trace_of(payloads: &[&[u8]]) -> Trace {
+    let mut trace = Trace::new("run-proof", "Disclose one step.");
+    for (i, payload) in payloads.iter().enumerate() {
+        trace.push_step(&format!("step:{}", i), payload);
+    }
+    trace
+}
+
+const PAYLOADS: &[&[u8]] = &[b"public", b"sealed-a", b"result", b"sealed-b", b"sealed-c"];
+
+#[test]
+fn every_step_proves_against_the_root() {
+    let trace = trace_of(PAYLOADS);
+    let root = trace.merkle_root();
+    for (i, payload) in PAYLOADS.iter().enumerate() {
+        let proof = trace.inclusion_proof(i).unwrap();
+        assert_eq!(proof.size, 5);
+        assert!(proof.verify(&root));
+        assert!(proof.verify_payload(payload, &root));
+    }
+    assert_eq!(trace.inclusion_proof(5), None);
+}
+
+#[test]
+fn leaves_are_salted_per_step_and_per_key() {
+    let trace = trace_of(PAYLOADS).with_salt_key(SALT_KEY);
+    let root = trace.merkle_root();
+    let nonces: Vec<String> = (0..PAYLOADS.len())
+        .map(|i| trace.inclusion_proof(i).unwrap().nonce.unwrap())
+        .collect();
+    for (i, nonce) in nonces.iter().enumerate() {
+        assert_eq!(nonce.len(), 64);
+        assert!(!nonces[..i].contains(nonce));
+        assert!(trace.inclusion_proof(i).unwrap().verify(&root));
+    }
+
+    // Same steps, same key: same root; another key, or none: another root
+    assert_eq!(
+        trace_of(PAYLOADS).with_salt_key(SALT_KEY).merkle_root(),
+        root
+    );
+    assert_ne!(
+        trace_of(PAYLOADS).with_salt_key([8; 32]).merkle_root(),
+        root
+    );
+    assert_ne!(trace_of(PAYLOADS).merkle_root(), root);
+    assert_eq!(trace_of(PAYLOADS).inclusion_proof(0).unwrap().nonce, None);
+}
+
+#[test]
+fn disclosure_reveals_only_the_step() {
+    let trace = trace_of(PAYLOADS);
+    let root = trace.merkle_root();
+    let proof = trace.inclusion_proof(2).unwrap();
+
+    // What a regulator receives: one step, sibling hashes, nothing else
+    let json = serde_json::to_string(&proof).unwrap();
+    for sealed in ["sealed-a", "sealed-b", "sealed-c", "step:1", "step:3"] {
+        assert!(!json.contains(sealed));
+    }
+    let received: StepProof = serde_json::from_str(&json).unwrap();
+    assert!(received.verify_payload(b"result", &root));
+    assert!(!received.verify_payload(b"resulT", &root));
+}
+
+#[test]
+fn forged_proofs_fail() {
+    let trace = trace_of(PAYLOADS);
+    let root = trace.merkle_root();
+    let proof = trace.inclusion_proof(2).unwrap();
+
+    let mut renamed = proof.clone();
+    renamed.step.name = "step:9".to_string();
+    assert!(!renamed.verify(&root));
+
+    let mut moved = proof.clone();
+    moved.index = 3;
+    assert!(!moved.verify(&root));
+
+    let mut garbled = proof.clone();
+    garbled.path[0] = "zz".to_string();
+    assert!(!garbled.verify(&root));
+
+    // Each salted leaf has its own nonce: another step's does not fit,
+    // and neither does none
+    let salted = trace_of(PAYLOADS).with_salt_key(SALT_KEY);
+    let mut resalted = salted.inclusion_proof(2).unwrap();
+    resalted.nonce = salted.inclusion_proof(3).unwrap().nonce;
+    assert!(!resalted.verify(&salted.merkle_root()));
+    resalted.nonce = None;
+    assert!(!resalted.verify(&salted.merkle_root()));
+
+    let other = trace_of(&[b"public", b"sealed-a", b"other", b"sealed-b", b"sealed-c"]);
+    assert_ne!(other.merkle_root(), root);
+    assert!(!proof.verify(&other.merkle_root()));
+    assert!(!proof.verify("not hex"));
+}
+
+#[test]
+fn receipts_carry_the_root() {
+    let ex = executor().with_salt_key(SALT_KEY);
+    let request = request("intent-proof", counter_inputs(3, 1), Constraints::default());
+    let resp = ex.execute(&request).unwrap();
+    resp.verify().unwrap();
+    let payload = resp.payload.unwrap();
+    assert_eq!(payload.merkle_root().len(), 64);
+    assert!(payload.receipt.salted);
+
+    // Salted under a fixed key, the run is as deterministic as ever
+    assert_eq!(ex.execute(&request).unwrap().payload, Some(payload.clone()));
+
+    // Logs alone cannot mint proofs: the runner's key is needed
+    let receipt = Receipt::from_payload(&payload.receipt, resp.logs.as_deref());
+    assert_eq!(receipt.inclusion_proof(2), None);
+    let receipt = receipt.with_salt_key(SALT_KEY);
+    let proof = receipt.inclusion_proof(2).unwrap();
+    assert_eq!(proof.step.name, "counter_v1:1");
+    assert!(receipt.verify_step(&proof));
+    assert!(proof.verify(payload.merkle_root()));
+
+    let mut sealed_away = receipt.clone();
+    sealed_away.merkle_root = "00".repeat(32);
+    assert!(!sealed_away.verify_step(&proof));
+}
+
+#[test]
+fn unsalted_runs_prove_from_their_logs() {
+    let resp = executor()
+        .execute(&request(
+            "intent-open",
+            counter_inputs(2, 1),
+            Constraints::default(),
+        ))
+        .unwrap();
+    let payload = resp.payload.unwrap();
+    assert!(!payload.receipt.salted);
+
+    let receipt = Receipt::from_payload(&payload.receipt, resp.logs.as_deref());
+    let proof = receipt.inclusion_proof(1).unwrap();
+    assert_eq!(proof.nonce, None);
+    assert!(proof.verify(payload.merkle_root()));
+}
@@ -1,8 +1,225 @@
-/// Pilgrim D.R.E. — Deterministic Runtime Enforcement
+//! Pilgrim D.R.E. — Deterministic Runtime Enforcement
+//!
+//! This function is intentionally brutal.
+//! If enforcement fails, execution halts immediately.
+//! No retry. No interpretation.
+//!
+//! The only thing written on the way down is a fixed-size, keyed halt
+//! record (see `install`), so a post-mortem can tell a D.R.E. halt from
+//! a crash. Writing it never allocates.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs::File;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Halt record schema version (bump on any layout change).
+pub const HALT_RECORD_VERSION: u16 = 1;
+
+/// Encoded size of a halt record, in bytes.
+pub const HALT_RECORD_LEN: usize = 56;
+
+const MAGIC: [u8; 8] = *b"PLGRHALT";
+const BODY_LEN: usize = 24;
+
+/// Why the runtime halted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum HaltReason {
+    Unspecified = 0,
+    SentinelDrift = 1,
+    InvariantViolated = 2,
+    Unserializable = 3,
+    LedgerWriteFailed = 4,
+}
+
+impl HaltReason {
+    pub const fn code(self) -> u16 {
+        self as u16
+    }
+
+    pub const fn from_code(code: u16) -> Option<Self> {
+        match code {
+            0 => Some(HaltReason::Unspecified),
+            1 => Some(HaltReason::SentinelDrift),
+            2 => Some(HaltReason::InvariantViolated),
+            3 => Some(HaltReason::Unserializable),
+            4 => Some(HaltReason::LedgerWriteFailed),
+            _ => None,
+        }
+    }
+}
+
+/// Fixed-size halt record.
+///
+/// Layout (little endian, `HALT_RECORD_LEN` bytes):
+/// magic `PLGRHALT` | version u16 | reason u16 | pid u32 | unix_ms u64 | mac [u8; 32]
 ///
-/// This function is intentionally brutal.
-/// If enforcement fails, execution halts immediately.
-/// No logging. No retry. No interpretation.
+/// `mac` = HMAC-SHA256 over the first 24 bytes, keyed with the key given
+/// to `install`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HaltRecord {
+    pub version: u16,
+    pub reason: HaltReason,
+    pub pid: u32,
+    pub unix_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltRecordError {
+    /// No record present (slot never written, or zeroed file region).
+    Empty,
+    BadMagic,
+    UnsupportedVersion(u16),
+    UnknownReason(u16),
+    BadMac,
+}
+
+impl HaltRecord {
+    pub fn new(reason: HaltReason, pid: u32, unix_ms: u64) -> Self {
+        Self {
+            version: HALT_RECORD_VERSION,
+            reason,
+            pid,
+            unix_ms,
+        }
+    }
+
+    pub fn encode(&self, key: &[u8; 32]) -> [u8; HALT_RECORD_LEN] {
+        let mut out = [0u8; HALT_RECORD_LEN];
+        out[0..8].copy_from_slice(&MAGIC);
+        out[8..10].copy_from_slice(&self.version.to_le_bytes());
+        out[10..12].copy_from_slice(&self.reason.code().to_le_bytes());
+        out[12..16].copy_from_slice(&self.pid.to_le_bytes());
+        out[16..24].copy_from_slice(&self.unix_ms.to_le_bytes());
+
+        let mac = hmac(key, &out[..BODY_LEN]).finalize().into_bytes();
+        out[BODY_LEN..].copy_from_slice(&mac);
+        out
+    }
+
+    /// Decode and authenticate a record written by `enforce_with`.
+    pub fn decode(bytes: &[u8; HALT_RECORD_LEN], key: &[u8; 32]) -> Result<Self, HaltRecordError> {
+        if bytes.iter().all(|b| *b == 0) {
+            return Err(HaltRecordError::Empty);
+        }
+        if bytes[0..8] != MAGIC {
+            return Err(HaltRecordError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes([bytes[8], bytes[9]]);
+        if version != HALT_RECORD_VERSION {
+            return Err(HaltRecordError::UnsupportedVersion(version));
+        }
+        hmac(key, &bytes[..BODY_LEN])
+            .verify_slice(&bytes[BODY_LEN..])
+            .map_err(|_| HaltRecordError::BadMac)?;
+
+        let code = u16::from_le_bytes([bytes[10], bytes[11]]);
+        let reason = HaltReason::from_code(code).ok_or(HaltRecordError::UnknownReason(code))?;
+        let pid = u32::from_le_bytes(bytes[12..16].try_into().expect("fixed slice"));
+        let unix_ms = u64::from_le_bytes(bytes[16..24].try_into().expect("fixed slice"));
+
+        Ok(Self {
+            version,
+            reason,
+            pid,
+            unix_ms,
+        })
+    }
+}
+
+fn hmac(key: &[u8; 32], body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(body);
+    mac
+}
+
+/// Static memory region that can hold one halt record.
+///
+/// Declare it as a `static` so it is allocated before anything can fail:
+/// `static SLOT: HaltSlot = HaltSlot::new();`
+pub struct HaltSlot {
+    bytes: [AtomicU8; HALT_RECORD_LEN],
+}
+
+impl HaltSlot {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [const { AtomicU8::new(0) }; HALT_RECORD_LEN],
+        }
+    }
+
+    pub fn write(&self, record: &[u8; HALT_RECORD_LEN]) {
+        for (cell, b) in self.bytes.iter().zip(record) {
+            cell.store(*b, Ordering::SeqCst);
+        }
+    }
+
+    pub fn read(&self) -> [u8; HALT_RECORD_LEN] {
+        let mut out = [0u8; HALT_RECORD_LEN];
+        for (b, cell) in out.iter_mut().zip(&self.bytes) {
+            *b = cell.load(Ordering::SeqCst);
+        }
+        out
+    }
+}
+
+impl Default for HaltSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where halt records go. Opened up front, never at halt time.
+pub enum HaltSink {
+    /// Pre-opened file; open it in append mode. Records are fsync'd.
+    File(File),
+    /// Static memory region (e.g. one inspected by a supervisor).
+    Memory(&'static HaltSlot),
+}
+
+struct Installed {
+    sink: HaltSink,
+    key: [u8; 32],
+}
+
+static INSTALLED: OnceLock<Installed> = OnceLock::new();
+
+/// Install the halt sink and MAC key. First call wins.
+///
+/// Returns `false` if a sink was already installed.
+pub fn install(sink: HaltSink, key: [u8; 32]) -> bool {
+    INSTALLED.set(Installed { sink, key }).is_ok()
+}
+
+/// Write a halt record to the installed sink, if any. Never allocates.
+///
+/// Returns `true` if a record was written.
+pub fn record_halt(reason: HaltReason) -> bool {
+    let Some(installed) = INSTALLED.get() else {
+        return false;
+    };
+
+    let unix_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let bytes = HaltRecord::new(reason, std::process::id(), unix_ms).encode(&installed.key);
+
+    match &installed.sink {
+        HaltSink::File(file) => {
+            let mut file: &File = file;
+            file.write_all(&bytes).is_ok() && file.sync_data().is_ok()
+        }
+        HaltSink::Memory(slot) => {
+            slot.write(&bytes);
+            true
+        }
+    }
+}
 
 pub fn enforce(allowed: bool) -> ! {
     if allowed {
@@ -11,6 +228,59 @@ pub fn enforce(allowed: bool) -> ! {
         panic!("D.R.E. misuse: enforce(true) is invalid");
     }
 
+    enforce_with(HaltReason::Unspecified)
+}
+
+/// Halt with a recorded reason.
+/// The record is written first; the halt happens whether or not it succeeds.
+pub fn enforce_with(reason: HaltReason) -> ! {
+    record_halt(reason);
+
     // Deterministic hard stop
     std::process::abort();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    #[test]
+    fn halt_record_roundtrips() {
+        let record = HaltRecord::new(HaltReason::SentinelDrift, 42, 1_700_000_000_000);
+        let bytes = record.encode(&KEY);
+        assert_eq!(HaltRecord::decode(&bytes, &KEY), Ok(record));
+    }
+
+    #[test]
+    fn halt_record_mac_is_hmac_sha256() {
+        let bytes = HaltRecord::new(HaltReason::InvariantViolated, 1, 2).encode(&KEY);
+        assert_eq!(
+            hex::encode(&bytes[BODY_LEN..]),
+            "81adc772ff46e64a5af54a51d69e1652c8d3dd5dffef44ddac4e211fc6f36b13"
+        );
+    }
+
+    #[test]
+    fn halt_record_rejects_wrong_key_and_tamper() {
+        let mut bytes = HaltRecord::new(HaltReason::InvariantViolated, 1, 2).encode(&KEY);
+        assert_eq!(HaltRecord::decode(&bytes, &[0; 32]), Err(HaltRecordError::BadMac));
+
+        bytes[10] = HaltReason::Unspecified.code() as u8;
+        assert_eq!(HaltRecord::decode(&bytes, &KEY), Err(HaltRecordError::BadMac));
+    }
+
+    #[test]
+    fn memory_slot_holds_the_record() {
+        static SLOT: HaltSlot = HaltSlot::new();
+        assert_eq!(HaltRecord::decode(&SLOT.read(), &KEY), Err(HaltRecordError::Empty));
+
+        assert!(install(HaltSink::Memory(&SLOT), KEY));
+        assert!(record_halt(HaltReason::LedgerWriteFailed));
+
+        let record = HaltRecord::decode(&SLOT.read(), &KEY).unwrap();
+        assert_eq!(record.reason, HaltReason::LedgerWriteFailed);
+        assert_eq!(record.pid, std::process::id());
+    }
+}
@@ -1,30 +1,145 @@
-use crate::*;
+//! Bridge-facing helpers for Pilgrim Handshake.
+//! This layer must never assume internal-only types.
 
-/// Bridge-facing helpers for Pilgrim Handshake.
-/// This layer must never assume internal-only types.
+use crate::*;
+use serde_json::{Map, Value};
 
 impl Constraints {
+    /// Upper bound accepted for `max_steps` from bridge callers.
+    pub const MAX_STEPS_LIMIT: u32 = 10_000_000;
+    /// Upper bound accepted for `max_runtime_ms` from bridge callers (24 h).
+    pub const MAX_RUNTIME_MS_LIMIT: u64 = 86_400_000;
+
     /// Deterministic constructor for bridge callers.
-    pub fn from_json_string(_s: &str) -> Self {
-        Constraints {
-            max_steps: 0,
-            max_runtime_ms: 0,
-            require_logs: false,
-            ..Default::default()
+    ///
+    /// Expects a JSON object with exactly `max_steps`, `max_runtime_ms`,
+    /// `require_logs` and `privacy`. Nothing is defaulted: a zero limit
+    /// would make every run fail, so limits must be in `1..=LIMIT`.
+    /// Every bad field is reported, in field order.
+    pub fn from_json_string(s: &str) -> Result<Self, HandshakeError> {
+        let value: Value = serde_json::from_str(s).map_err(|_| HandshakeError::BadJson)?;
+        let obj = value.as_object().ok_or(HandshakeError::BadJson)?;
+
+        let mut errors = Vec::new();
+        let max_steps = bounded(obj, "max_steps", Self::MAX_STEPS_LIMIT.into(), &mut errors);
+        let max_runtime_ms = bounded(
+            obj,
+            "max_runtime_ms",
+            Self::MAX_RUNTIME_MS_LIMIT,
+            &mut errors,
+        );
+        let require_logs = field(obj, "require_logs", "bool", Value::as_bool, &mut errors);
+        let privacy = field(obj, "privacy", "string", Value::as_str, &mut errors)
+            .and_then(|tier| privacy_tier(tier, &mut errors));
+
+        const KNOWN: [&str; 4] = ["max_steps", "max_runtime_ms", "require_logs", "privacy"];
+        for key in obj.keys().filter(|k| !KNOWN.contains(&k.as_str())) {
+            errors.push(FieldError {
+                field: key.clone(),
+                problem: FieldProblem::UnknownField,
+            });
+        }
+
+        match (max_steps, max_runtime_ms, require_logs, privacy) {
+            (Some(max_steps), Some(max_runtime_ms), Some(require_logs), Some(privacy))
+                if errors.is_empty() =>
+            {
+                Ok(Constraints {
+                    max_steps: max_steps as u32,
+                    max_runtime_ms,
+                    require_logs,
+                    privacy,
+                })
+            }
+            _ => Err(HandshakeError::InvalidConstraints(errors)),
+        }
+    }
+}
+
+fn field<'a, T>(
+    obj: &'a Map<String, Value>,
+    name: &str,
+    expected: &'static str,
+    get: impl Fn(&'a Value) -> Option<T>,
+    errors: &mut Vec<FieldError>,
+) -> Option<T> {
+    let Some(value) = obj.get(name) else {
+        errors.push(FieldError {
+            field: name.to_string(),
+            problem: FieldProblem::Missing,
+        });
+        return None;
+    };
+    let got = get(value);
+    if got.is_none() {
+        errors.push(FieldError {
+            field: name.to_string(),
+            problem: FieldProblem::WrongType { expected },
+        });
+    }
+    got
+}
+
+/// Unsigned integer in `1..=max`. Negative or oversized numbers are out of range.
+fn bounded(
+    obj: &Map<String, Value>,
+    name: &str,
+    max: u64,
+    errors: &mut Vec<FieldError>,
+) -> Option<u64> {
+    let n = field(obj, name, "integer", |v| v.as_number().cloned(), errors)?;
+    let in_range = n.as_u64().filter(|v| (1..=max).contains(v));
+    if in_range.is_none() {
+        errors.push(FieldError {
+            field: name.to_string(),
+            problem: if n.is_f64() {
+                FieldProblem::WrongType {
+                    expected: "integer",
+                }
+            } else {
+                FieldProblem::OutOfRange { min: 1, max }
+            },
+        });
+    }
+    in_range
+}
+
+fn privacy_tier(tier: &str, errors: &mut Vec<FieldError>) -> Option<PrivacyTier> {
+    match tier {
+        "Public" => Some(PrivacyTier::Public),
+        "Protected" => Some(PrivacyTier::Protected),
+        "Confidential" => Some(PrivacyTier::Confidential),
+        "Sealed" => Some(PrivacyTier::Sealed),
+        other => {
+            errors.push(FieldError {
+                field: "privacy".to_string(),
+                problem: FieldProblem::UnknownVariant {
+                    got: other.to_string(),
+                },
+            });
+            None
         }
     }
 }
 
 impl RequestEnvelope {
     /// Canonical bytes used for sealing and verification.
+    /// `{"intent": ..., "protocol": ...}` as RFC 8785 (JCS), or as
+    /// deterministic CBOR when `checksum.algo` is `Sha256Cbor`; the checksum
+    /// is SHA-256 of exactly these bytes. `/1` envelopes keep their legacy
+    /// `serde_json` bytes.
     /// Must be stable across platforms.
-    pub fn seal_bytes(&self) -> Vec<u8> {
-        serde_json::to_vec(&(self.protocol.clone(), &self.intent))
-            .expect("serialization cannot fail")
+    pub fn seal_bytes(&self) -> Result<Vec<u8>, HandshakeError> {
+        // Hash only stable fields (exclude checksum + signature to avoid recursion)
+        let to_hash = RequestToHash {
+            protocol: self.protocol.clone(),
+            intent: self.intent.clone(),
+        };
+        sealed_bytes(&self.protocol, self.checksum.algo, &to_hash)
     }
 
     /// Alias for bridge / FFI consumers.
-    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+    pub fn to_canonical_bytes(&self) -> Result<Vec<u8>, HandshakeError> {
         self.seal_bytes()
     }
 }
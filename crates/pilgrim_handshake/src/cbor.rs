@@ -0,0 +1,371 @@
+//! Deterministic CBOR (RFC 8949 §4.2.1) for handshake envelopes.
+//!
+//! Encoding rules:
+//! - integers, lengths and floats in their shortest form
+//! - definite lengths only
+//! - map keys sorted by their encoded bytes, no duplicates
+//!
+//! The data model is JSON's (`serde_json::Value`), so JSON ↔ CBOR is
+//! lossless both ways. One JSON form has a CBOR type of its own: the typed
+//! datum `{"bytes": "<lowercase hex>"}` (`DatumValue::Bytes`) is a byte
+//! string, and a byte string decodes back to it.
+//!
+//! Decoding is strict: anything the encoder would not have produced
+//! (non-shortest forms, unsorted keys, tags, NaN/∞, indefinite lengths, a
+//! `{"bytes": ..}` map where a byte string belongs) is `BadCbor`, so one
+//! value has exactly one accepted byte form and checksums over it are
+//! unambiguous.
+
+use crate::HandshakeError;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+/// Deepest nesting accepted by `decode`.
+pub const MAX_DEPTH: usize = 128;
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+const SIMPLE: u8 = 7;
+
+/// Key of the typed-datum form carried as a byte string (see `datum`).
+const BYTES_KEY: &str = "bytes";
+
+/// Serialize `value` to canonical CBOR bytes.
+pub fn to_canonical_vec<T: Serialize>(value: &T) -> Result<Vec<u8>, HandshakeError> {
+    let value = serde_json::to_value(value).map_err(|_| HandshakeError::BadJson)?;
+    encode(&value)
+}
+
+/// Canonical CBOR bytes of a JSON value.
+pub fn encode(value: &Value) -> Result<Vec<u8>, HandshakeError> {
+    let mut out = Vec::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+/// Decode canonical CBOR into a JSON value.
+pub fn decode(bytes: &[u8]) -> Result<Value, HandshakeError> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let value = reader.value(0)?;
+    if reader.pos != bytes.len() {
+        return Err(HandshakeError::BadCbor);
+    }
+    Ok(value)
+}
+
+/// JSON text → canonical CBOR.
+pub fn json_to_cbor(json: &str) -> Result<Vec<u8>, HandshakeError> {
+    let value: Value = serde_json::from_str(json).map_err(|_| HandshakeError::BadJson)?;
+    encode(&value)
+}
+
+/// Canonical CBOR → JSON text.
+pub fn cbor_to_json(bytes: &[u8]) -> Result<String, HandshakeError> {
+    serde_json::to_string(&decode(bytes)?).map_err(|_| HandshakeError::BadJson)
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, n: u64) {
+    let major = major << 5;
+    match n {
+        0..=23 => out.push(major | n as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, n as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) -> Result<(), HandshakeError> {
+    match value {
+        Value::Null => out.push(0xf6),
+        Value::Bool(false) => out.push(0xf4),
+        Value::Bool(true) => out.push(0xf5),
+        Value::Number(n) => write_number(out, n)?,
+        Value::String(s) => {
+            write_head(out, TEXT, s.len() as u64);
+            out.extend_from_slice(s.as_bytes());
+        }
+        Value::Array(items) => {
+            write_head(out, ARRAY, items.len() as u64);
+            for item in items {
+                write_value(out, item)?;
+            }
+        }
+        Value::Object(map) => {
+            if let Some(bytes) = byte_string(map) {
+                write_head(out, BYTES, bytes.len() as u64);
+                out.extend_from_slice(&bytes);
+                return Ok(());
+            }
+            let mut members = Vec::with_capacity(map.len());
+            for (key, item) in map {
+                let mut k = Vec::new();
+                write_head(&mut k, TEXT, key.len() as u64);
+                k.extend_from_slice(key.as_bytes());
+                members.push((k, item));
+            }
+            members.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            write_head(out, MAP, members.len() as u64);
+            for (k, item) in members {
+                out.extend_from_slice(&k);
+                write_value(out, item)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The bytes of `{"bytes": "<lowercase hex>"}`, if `map` is exactly that.
+fn byte_string(map: &Map<String, Value>) -> Option<Vec<u8>> {
+    let Some(Value::String(h)) = map.get(BYTES_KEY) else {
+        return None;
+    };
+    if map.len() != 1 || h.bytes().any(|b| b.is_ascii_uppercase()) {
+        return None;
+    }
+    hex::decode(h).ok()
+}
+
+fn write_number(out: &mut Vec<u8>, n: &Number) -> Result<(), HandshakeError> {
+    if let Some(u) = n.as_u64() {
+        write_head(out, UNSIGNED, u);
+    } else if let Some(i) = n.as_i64() {
+        // i < 0 here: encoded as -1 - n
+        write_head(out, NEGATIVE, !(i as u64));
+    } else {
+        let f = n.as_f64().ok_or(HandshakeError::NumberOutOfRange)?;
+        write_float(out, f)?;
+    }
+    Ok(())
+}
+
+fn write_float(out: &mut Vec<u8>, f: f64) -> Result<(), HandshakeError> {
+    if !f.is_finite() {
+        return Err(HandshakeError::NumberOutOfRange);
+    }
+    if let Some(half) = f16_bits(f) {
+        out.push(0xf9);
+        out.extend_from_slice(&half.to_be_bytes());
+    } else if (f as f32) as f64 == f {
+        out.push(0xfa);
+        out.extend_from_slice(&(f as f32).to_bits().to_be_bytes());
+    } else {
+        out.push(0xfb);
+        out.extend_from_slice(&f.to_bits().to_be_bytes());
+    }
+    Ok(())
+}
+
+/// IEEE half-precision bits of `f`, if `f` is exactly representable.
+fn f16_bits(f: f64) -> Option<u16> {
+    let bits = f.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    let mant = bits & ((1 << 52) - 1);
+
+    if exp == 0 && mant == 0 {
+        return Some(sign);
+    }
+    if exp == 0 {
+        // f64 subnormals are far below the f16 range
+        return None;
+    }
+
+    let e = exp - 1023;
+    if (-14..=15).contains(&e) {
+        // Normal half: 10 mantissa bits
+        if mant & ((1 << 42) - 1) != 0 {
+            return None;
+        }
+        return Some(sign | (((e + 15) as u16) << 10) | (mant >> 42) as u16);
+    }
+    if (-24..-14).contains(&e) {
+        // Subnormal half: value = m × 2^-24 with m < 2^10
+        let full = mant | (1 << 52);
+        let shift = 52 - (e + 24);
+        if full & ((1u64 << shift) - 1) != 0 {
+            return None;
+        }
+        return Some(sign | (full >> shift) as u16);
+    }
+    None
+}
+
+fn f16_to_f64(half: u16) -> f64 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = ((half >> 10) & 0x1f) as i32;
+    let mant = (half & 0x3ff) as f64;
+    match exp {
+        0 => sign * mant * 2f64.powi(-24),
+        31 if mant == 0.0 => sign * f64::INFINITY,
+        31 => f64::NAN,
+        _ => sign * (1.0 + mant / 1024.0) * 2f64.powi(exp - 15),
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], HandshakeError> {
+        let end = self.pos.checked_add(n).ok_or(HandshakeError::BadCbor)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(HandshakeError::BadCbor)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Major type, additional info, and the argument (shortest form enforced).
+    fn head(&mut self) -> Result<(u8, u8, u64), HandshakeError> {
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        if major == SIMPLE {
+            return Ok((major, info, 0));
+        }
+        let n = match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().expect("2 bytes")) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().expect("4 bytes")) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().expect("8 bytes")),
+            _ => return Err(HandshakeError::BadCbor),
+        };
+        let shortest = match info {
+            24 => n >= 24,
+            25 => n > 0xff,
+            26 => n > 0xffff,
+            27 => n > 0xffff_ffff,
+            _ => true,
+        };
+        if !shortest {
+            return Err(HandshakeError::BadCbor);
+        }
+        Ok((major, info, n))
+    }
+
+    fn len(&self, n: u64) -> Result<usize, HandshakeError> {
+        // Every item takes at least one byte, so no length can exceed the input
+        usize::try_from(n)
+            .ok()
+            .filter(|n| *n <= self.bytes.len() - self.pos)
+            .ok_or(HandshakeError::BadCbor)
+    }
+
+    fn text(&mut self, n: u64) -> Result<String, HandshakeError> {
+        let n = self.len(n)?;
+        let raw = self.take(n)?;
+        String::from_utf8(raw.to_vec()).map_err(|_| HandshakeError::BadCbor)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, HandshakeError> {
+        if depth > MAX_DEPTH {
+            return Err(HandshakeError::BadCbor);
+        }
+        let (major, info, n) = self.head()?;
+        match major {
+            UNSIGNED => Ok(Value::from(n)),
+            NEGATIVE => {
+                let i = i64::try_from(n).map_err(|_| HandshakeError::NumberOutOfRange)?;
+                Ok(Value::from(-1 - i))
+            }
+            BYTES => {
+                let n = self.len(n)?;
+                let mut map = Map::new();
+                map.insert(
+                    BYTES_KEY.to_string(),
+                    Value::String(hex::encode(self.take(n)?)),
+                );
+                Ok(Value::Object(map))
+            }
+            TEXT => Ok(Value::String(self.text(n)?)),
+            ARRAY => {
+                let n = self.len(n)?;
+                let mut items = Vec::with_capacity(n);
+                for _ in 0..n {
+                    items.push(self.value(depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+            MAP => {
+                let n = self.len(n)?;
+                let mut map = Map::new();
+                let mut prev_key: Option<&[u8]> = None;
+                for _ in 0..n {
+                    let start = self.pos;
+                    let (kmajor, _, klen) = self.head()?;
+                    if kmajor != TEXT {
+                        return Err(HandshakeError::BadCbor);
+                    }
+                    let key = self.text(klen)?;
+                    let encoded = &self.bytes[start..self.pos];
+                    if prev_key.is_some_and(|prev| prev >= encoded) {
+                        // Unsorted or duplicate key
+                        return Err(HandshakeError::BadCbor);
+                    }
+                    prev_key = Some(encoded);
+                    map.insert(key, self.value(depth + 1)?);
+                }
+                if byte_string(&map).is_some() {
+                    // Encoded as a byte string, never as a map
+                    return Err(HandshakeError::BadCbor);
+                }
+                Ok(Value::Object(map))
+            }
+            SIMPLE => self.simple(info),
+            TAG => Err(HandshakeError::BadCbor),
+            _ => unreachable!("major type is 3 bits"),
+        }
+    }
+
+    fn simple(&mut self, info: u8) -> Result<Value, HandshakeError> {
+        let f = match info {
+            20 => return Ok(Value::Bool(false)),
+            21 => return Ok(Value::Bool(true)),
+            22 => return Ok(Value::Null),
+            25 => f16_to_f64(u16::from_be_bytes(
+                self.take(2)?.try_into().expect("2 bytes"),
+            )),
+            26 => f32::from_bits(u32::from_be_bytes(
+                self.take(4)?.try_into().expect("4 bytes"),
+            )) as f64,
+            27 => f64::from_bits(u64::from_be_bytes(
+                self.take(8)?.try_into().expect("8 bytes"),
+            )),
+            _ => return Err(HandshakeError::BadCbor),
+        };
+
+        // Only finite floats, and only in the width the encoder would pick
+        let mut canonical = Vec::new();
+        write_float(&mut canonical, f).map_err(|_| HandshakeError::BadCbor)?;
+        let width = match info {
+            25 => 3,
+            26 => 5,
+            _ => 9,
+        };
+        if canonical.len() != width {
+            return Err(HandshakeError::BadCbor);
+        }
+        Number::from_f64(f)
+            .map(Value::Number)
+            .ok_or(HandshakeError::BadCbor)
+    }
+}
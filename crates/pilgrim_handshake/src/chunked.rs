@@ -0,0 +1,361 @@
+//! Chunked transport for intents with large payloads.
+//!
+//! A transfer is one `ManifestEnvelope` plus `chunk_count` `ChunkEnvelope`s.
+//! The manifest carries the intent, the payload length, the chunk size and
+//! the Merkle root over all chunks (see `merkle`); each chunk carries its
+//! bytes and an inclusion proof against that root. So:
+//! - every envelope is self-checking (own checksum, like `RequestEnvelope`)
+//! - every chunk verifies on its own, in any order, against the manifest
+//! - neither side ever holds the whole payload
+//!
+//! Sending: feed the payload through `ChunkedUpload::update` (one pass,
+//! only hashes are kept), `finish` to get the manifest, then
+//! `ChunkedUpload::chunk` for each index the receiver is still missing.
+//!
+//! Receiving: `ChunkReceiver::new(manifest)`, `accept` each chunk and write
+//! the returned bytes at `offset`. After a restart, `ChunkReceiver::resume`
+//! with the indices already stored; `missing` is what to ask for again.
+
+use crate::merkle::{self, Hash, MerkleTree};
+use crate::{
+    canonical_bytes, sha256_hex, version, Checksum, HandshakeError, HashAlgo, Intent,
+    PROTOCOL_VERSION,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Default chunk size: 1 MiB.
+pub const DEFAULT_CHUNK_SIZE: u32 = 1 << 20;
+
+/// Opens a transfer: what will run, and what the chunks must add up to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEnvelope {
+    pub protocol: String,
+    pub intent: Intent,
+    /// Total payload bytes across all chunks.
+    pub payload_len: u64,
+    /// Bytes per chunk (the last chunk may be shorter).
+    pub chunk_size: u32,
+    pub chunk_count: u32,
+    /// Merkle root over the chunk leaf hashes (hex).
+    pub merkle_root: String,
+    pub checksum: Checksum,
+}
+
+/// One slice of the payload, provable against the manifest's root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkEnvelope {
+    pub protocol: String,
+    pub intent_id: String,
+    pub index: u32,
+    /// Chunk bytes, lowercase hex.
+    pub data_hex: String,
+    /// Sibling hashes from this leaf to the root (hex).
+    pub proof: Vec<String>,
+    pub checksum: Checksum,
+}
+
+/// Internal structs: explicitly define what gets hashed (excludes checksum fields).
+#[derive(Serialize)]
+struct ManifestToHash<'a> {
+    protocol: &'a str,
+    intent: &'a Intent,
+    payload_len: u64,
+    chunk_size: u32,
+    chunk_count: u32,
+    merkle_root: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChunkToHash<'a> {
+    protocol: &'a str,
+    intent_id: &'a str,
+    index: u32,
+    data_hex: &'a str,
+    proof: &'a [String],
+}
+
+impl ManifestEnvelope {
+    fn compute_checksum_hex(&self) -> Result<String, HandshakeError> {
+        let to_hash = ManifestToHash {
+            protocol: &self.protocol,
+            intent: &self.intent,
+            payload_len: self.payload_len,
+            chunk_size: self.chunk_size,
+            chunk_count: self.chunk_count,
+            merkle_root: &self.merkle_root,
+        };
+        Ok(sha256_hex(&canonical_bytes(self.checksum.algo, &to_hash)?))
+    }
+
+    /// Verify protocol, checksum, and that the chunk layout adds up.
+    pub fn verify(&self) -> Result<(), HandshakeError> {
+        version::check_protocol(&self.protocol)?;
+        if self.compute_checksum_hex()? != self.checksum.hex {
+            return Err(HandshakeError::ChecksumMismatch);
+        }
+        if self.chunk_size == 0
+            || self.payload_len.div_ceil(self.chunk_size as u64) != self.chunk_count as u64
+            || decode_hash(&self.merkle_root).is_none()
+        {
+            return Err(HandshakeError::BadManifest);
+        }
+        Ok(())
+    }
+
+    /// Byte length of chunk `index`.
+    pub fn chunk_len(&self, index: u32) -> u64 {
+        let start = self.chunk_offset(index);
+        self.payload_len
+            .saturating_sub(start)
+            .min(self.chunk_size as u64)
+    }
+
+    /// Payload offset of chunk `index`.
+    pub fn chunk_offset(&self, index: u32) -> u64 {
+        index as u64 * self.chunk_size as u64
+    }
+}
+
+impl ChunkEnvelope {
+    fn compute_checksum_hex(&self) -> Result<String, HandshakeError> {
+        let to_hash = ChunkToHash {
+            protocol: &self.protocol,
+            intent_id: &self.intent_id,
+            index: self.index,
+            data_hex: &self.data_hex,
+            proof: &self.proof,
+        };
+        Ok(sha256_hex(&canonical_bytes(self.checksum.algo, &to_hash)?))
+    }
+
+    /// Verify protocol + checksum (not membership: see `ChunkReceiver`).
+    pub fn verify(&self) -> Result<(), HandshakeError> {
+        version::check_protocol(&self.protocol)?;
+        if self.compute_checksum_hex()? != self.checksum.hex {
+            return Err(HandshakeError::ChecksumMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Sender side: hashes the payload in one pass, then mints chunks on demand.
+#[derive(Debug, Clone)]
+pub struct ChunkedUpload {
+    chunk_size: u32,
+    algo: HashAlgo,
+    payload_len: u64,
+    pending: Vec<u8>,
+    leaves: Vec<Hash>,
+    tree: Option<MerkleTree>,
+    manifest: Option<ManifestEnvelope>,
+}
+
+impl ChunkedUpload {
+    /// `chunk_size` of 0 is treated as `DEFAULT_CHUNK_SIZE`.
+    pub fn new(chunk_size: u32, algo: HashAlgo) -> Self {
+        let chunk_size = if chunk_size == 0 {
+            DEFAULT_CHUNK_SIZE
+        } else {
+            chunk_size
+        };
+        Self {
+            chunk_size,
+            algo,
+            payload_len: 0,
+            pending: Vec::new(),
+            leaves: Vec::new(),
+            tree: None,
+            manifest: None,
+        }
+    }
+
+    /// Append payload bytes (any split; at most one chunk is buffered).
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        let size = self.chunk_size as usize;
+        self.payload_len += bytes.len() as u64;
+        while !bytes.is_empty() {
+            let take = (size - self.pending.len()).min(bytes.len());
+            self.pending.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+            if self.pending.len() == size {
+                self.leaves.push(merkle::leaf_hash(&self.pending));
+                self.pending.clear();
+            }
+        }
+    }
+
+    /// Seal the manifest for `intent` over everything passed to `update`.
+    pub fn finish(&mut self, intent: Intent) -> Result<ManifestEnvelope, HandshakeError> {
+        if !self.pending.is_empty() {
+            self.leaves.push(merkle::leaf_hash(&self.pending));
+            self.pending = Vec::new();
+        }
+        let chunk_count =
+            u32::try_from(self.leaves.len()).map_err(|_| HandshakeError::BadManifest)?;
+        let tree = MerkleTree::from_leaves(std::mem::take(&mut self.leaves));
+
+        let mut manifest = ManifestEnvelope {
+            protocol: PROTOCOL_VERSION.to_string(),
+            intent,
+            payload_len: self.payload_len,
+            chunk_size: self.chunk_size,
+            chunk_count,
+            merkle_root: hex::encode(tree.root()),
+            checksum: Checksum {
+                algo: self.algo,
+                hex: String::new(),
+            },
+        };
+        manifest.checksum.hex = manifest.compute_checksum_hex()?;
+        self.tree = Some(tree);
+        self.manifest = Some(manifest.clone());
+        Ok(manifest)
+    }
+
+    /// Seal chunk `index`; `data` must be the same bytes hashed by `update`.
+    pub fn chunk(&self, index: u32, data: &[u8]) -> Result<ChunkEnvelope, HandshakeError> {
+        let (Some(tree), Some(manifest)) = (&self.tree, &self.manifest) else {
+            return Err(HandshakeError::BadManifest);
+        };
+        let proof = tree
+            .proof(index as usize)
+            .ok_or(HandshakeError::BadChunk { index })?;
+        if data.len() as u64 != manifest.chunk_len(index)
+            || !merkle::verify_proof(
+                index as u64,
+                tree.len() as u64,
+                &merkle::leaf_hash(data),
+                &proof,
+                &tree.root(),
+            )
+        {
+            return Err(HandshakeError::BadChunk { index });
+        }
+
+        let mut chunk = ChunkEnvelope {
+            protocol: PROTOCOL_VERSION.to_string(),
+            intent_id: manifest.intent.intent_id.clone(),
+            index,
+            data_hex: hex::encode(data),
+            proof: proof.iter().map(hex::encode).collect(),
+            checksum: Checksum {
+                algo: self.algo,
+                hex: String::new(),
+            },
+        };
+        chunk.checksum.hex = chunk.compute_checksum_hex()?;
+        Ok(chunk)
+    }
+}
+
+/// A chunk that passed every check, ready to be written out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedChunk {
+    pub index: u32,
+    pub offset: u64,
+    pub data: Vec<u8>,
+}
+
+/// Receiver side: verifies chunks against a manifest and tracks progress.
+#[derive(Debug, Clone)]
+pub struct ChunkReceiver {
+    manifest: ManifestEnvelope,
+    root: Hash,
+    received: BTreeSet<u32>,
+}
+
+impl ChunkReceiver {
+    pub fn new(manifest: ManifestEnvelope) -> Result<Self, HandshakeError> {
+        Self::resume(manifest, [])
+    }
+
+    /// Continue a transfer whose chunks `received` are already stored.
+    pub fn resume(
+        manifest: ManifestEnvelope,
+        received: impl IntoIterator<Item = u32>,
+    ) -> Result<Self, HandshakeError> {
+        manifest.verify()?;
+        let root = decode_hash(&manifest.merkle_root).ok_or(HandshakeError::BadManifest)?;
+        let mut receiver = Self {
+            manifest,
+            root,
+            received: BTreeSet::new(),
+        };
+        for index in received {
+            if index >= receiver.manifest.chunk_count {
+                return Err(HandshakeError::BadChunk { index });
+            }
+            receiver.received.insert(index);
+        }
+        Ok(receiver)
+    }
+
+    pub fn manifest(&self) -> &ManifestEnvelope {
+        &self.manifest
+    }
+
+    /// Verify `chunk` against the manifest and mark it received.
+    ///
+    /// Re-sending a chunk is harmless: it verifies again and returns the
+    /// same bytes.
+    pub fn accept(&mut self, chunk: &ChunkEnvelope) -> Result<VerifiedChunk, HandshakeError> {
+        chunk.verify()?;
+        let index = chunk.index;
+        let bad = || HandshakeError::BadChunk { index };
+        if chunk.intent_id != self.manifest.intent.intent_id || index >= self.manifest.chunk_count {
+            return Err(bad());
+        }
+
+        let data = decode_hex(&chunk.data_hex).ok_or_else(bad)?;
+        let proof = chunk
+            .proof
+            .iter()
+            .map(|h| decode_hash(h))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(bad)?;
+        if data.len() as u64 != self.manifest.chunk_len(index)
+            || !merkle::verify_proof(
+                index as u64,
+                self.manifest.chunk_count as u64,
+                &merkle::leaf_hash(&data),
+                &proof,
+                &self.root,
+            )
+        {
+            return Err(bad());
+        }
+
+        self.received.insert(index);
+        Ok(VerifiedChunk {
+            index,
+            offset: self.manifest.chunk_offset(index),
+            data,
+        })
+    }
+
+    /// Indices still to be sent, ascending.
+    pub fn missing(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.manifest.chunk_count).filter(|i| !self.received.contains(i))
+    }
+
+    pub fn received(&self) -> impl Iterator<Item = u32> + '_ {
+        self.received.iter().copied()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.received.len() as u64 == self.manifest.chunk_count as u64
+    }
+}
+
+/// Lowercase hex only: one spelling per byte string.
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.bytes().any(|b| b.is_ascii_uppercase()) {
+        return None;
+    }
+    hex::decode(s).ok()
+}
+
+fn decode_hash(s: &str) -> Option<Hash> {
+    decode_hex(s)?.try_into().ok()
+}
@@ -0,0 +1,224 @@
+//! Typed `Datum` values.
+//!
+//! Wire form (one per value, so canonical bytes are unambiguous):
+//! - text: a bare JSON string (the handshake/1 form, so existing
+//!   envelopes and checksums are unchanged)
+//! - everything else: a single-key object naming the type
+//!
+//! ```text
+//! "hello"                                   Text
+//! {"int": -42}                              Integer
+//! {"decimal": {"units": 150, "scale": 2}}   Decimal (1.50)
+//! {"bool": true}                            Bool
+//! {"bytes": "00ff"}                         Bytes (lowercase hex; a CBOR byte string)
+//! {"list": ["a", {"int": 1}]}               List
+//! ```
+//!
+//! No floats: fractional values are fixed-point `Decimal`s. Under JCS
+//! sealing, integers (and decimal units) must stay within ±(2^53 − 1);
+//! `Sha256Cbor` sealing carries the full `i64` range.
+
+use serde::{Deserialize, Serialize};
+
+/// Largest `Decimal::scale` accepted (digits after the point).
+pub const MAX_DECIMAL_SCALE: u8 = 18;
+
+/// Deepest `List` nesting accepted.
+pub const MAX_LIST_DEPTH: usize = 16;
+
+/// Fixed-point decimal: `units × 10^-scale`.
+///
+/// The scale is part of the value (`1.5` and `1.50` are different data),
+/// so there is no normalization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Decimal {
+    pub units: i64,
+    pub scale: u8,
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = self.units.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let sign = if self.units < 0 { "-" } else { "" };
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, frac) = padded.split_at(padded.len() - scale);
+        write!(f, "{}{}.{}", sign, int, frac)
+    }
+}
+
+/// `DatumValue` variant, named as on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatumKind {
+    Int,
+    Decimal,
+    Bool,
+    Bytes,
+    Text,
+    List,
+}
+
+impl DatumKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DatumKind::Int => "int",
+            DatumKind::Decimal => "decimal",
+            DatumKind::Bool => "bool",
+            DatumKind::Bytes => "bytes",
+            DatumKind::Text => "text",
+            DatumKind::List => "list",
+        }
+    }
+}
+
+/// A typed input (or output) value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Repr", into = "Repr")]
+pub enum DatumValue {
+    Integer(i64),
+    Decimal(Decimal),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    Text(String),
+    List(Vec<DatumValue>),
+}
+
+impl DatumValue {
+    /// Check scale and nesting limits.
+    pub fn validate(&self) -> Result<(), String> {
+        self.validate_at(0)
+    }
+
+    fn validate_at(&self, depth: usize) -> Result<(), String> {
+        match self {
+            DatumValue::Decimal(d) if d.scale > MAX_DECIMAL_SCALE => Err(format!(
+                "decimal scale {} exceeds {}",
+                d.scale, MAX_DECIMAL_SCALE
+            )),
+            DatumValue::List(_) if depth >= MAX_LIST_DEPTH => {
+                Err(format!("list nesting exceeds {}", MAX_LIST_DEPTH))
+            }
+            DatumValue::List(items) => items.iter().try_for_each(|v| v.validate_at(depth + 1)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn kind(&self) -> DatumKind {
+        match self {
+            DatumValue::Integer(_) => DatumKind::Int,
+            DatumValue::Decimal(_) => DatumKind::Decimal,
+            DatumValue::Bool(_) => DatumKind::Bool,
+            DatumValue::Bytes(_) => DatumKind::Bytes,
+            DatumValue::Text(_) => DatumKind::Text,
+            DatumValue::List(_) => DatumKind::List,
+        }
+    }
+
+    /// Type name as used on the wire ("text" for bare strings).
+    pub fn type_name(&self) -> &'static str {
+        self.kind().name()
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            DatumValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            DatumValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for DatumValue {
+    fn from(s: &str) -> Self {
+        DatumValue::Text(s.to_string())
+    }
+}
+
+impl From<String> for DatumValue {
+    fn from(s: String) -> Self {
+        DatumValue::Text(s)
+    }
+}
+
+impl From<i64> for DatumValue {
+    fn from(i: i64) -> Self {
+        DatumValue::Integer(i)
+    }
+}
+
+impl From<bool> for DatumValue {
+    fn from(b: bool) -> Self {
+        DatumValue::Bool(b)
+    }
+}
+
+impl From<Decimal> for DatumValue {
+    fn from(d: Decimal) -> Self {
+        DatumValue::Decimal(d)
+    }
+}
+
+/// Wire representation (see module docs).
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Repr {
+    Text(String),
+    Tagged(Tagged),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+enum Tagged {
+    Int(i64),
+    Decimal(Decimal),
+    Bool(bool),
+    Bytes(String),
+    List(Vec<DatumValue>),
+}
+
+impl From<DatumValue> for Repr {
+    fn from(v: DatumValue) -> Self {
+        match v {
+            DatumValue::Text(s) => Repr::Text(s),
+            DatumValue::Integer(i) => Repr::Tagged(Tagged::Int(i)),
+            DatumValue::Decimal(d) => Repr::Tagged(Tagged::Decimal(d)),
+            DatumValue::Bool(b) => Repr::Tagged(Tagged::Bool(b)),
+            DatumValue::Bytes(b) => Repr::Tagged(Tagged::Bytes(hex::encode(b))),
+            DatumValue::List(items) => Repr::Tagged(Tagged::List(items)),
+        }
+    }
+}
+
+impl TryFrom<Repr> for DatumValue {
+    type Error = String;
+
+    fn try_from(repr: Repr) -> Result<Self, Self::Error> {
+        let value = match repr {
+            Repr::Text(s) => DatumValue::Text(s),
+            Repr::Tagged(Tagged::Int(i)) => DatumValue::Integer(i),
+            Repr::Tagged(Tagged::Decimal(d)) => DatumValue::Decimal(d),
+            Repr::Tagged(Tagged::Bool(b)) => DatumValue::Bool(b),
+            Repr::Tagged(Tagged::Bytes(h)) => {
+                // Lowercase only: one spelling per byte string
+                if h.bytes().any(|b| b.is_ascii_uppercase()) {
+                    return Err("bytes must be lowercase hex".to_string());
+                }
+                DatumValue::Bytes(hex::decode(&h).map_err(|e| e.to_string())?)
+            }
+            Repr::Tagged(Tagged::List(items)) => DatumValue::List(items),
+        };
+        value.validate()?;
+        Ok(value)
+    }
+}
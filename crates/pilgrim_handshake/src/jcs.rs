@@ -0,0 +1,194 @@
+//! JSON Canonicalization Scheme (RFC 8785).
+//!
+//! Canonical bytes are independent of struct field order, serde attributes
+//! and the producing language:
+//! - object members sorted by the UTF-16 code units of their names
+//! - no insignificant whitespace
+//! - strings with minimal escaping, otherwise literal UTF-8
+//! - numbers in ECMAScript `Number.prototype.toString` form
+//!
+//! Integers outside the I-JSON safe range (±(2^53 − 1)) have no exact
+//! canonical form and are rejected rather than rounded.
+//!
+//! Cross-language test vectors: `tests/vectors/jcs_v1.json`.
+
+use crate::HandshakeError;
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+/// Largest integer magnitude with an exact IEEE-754 double form.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serialize `value` to RFC 8785 canonical JSON bytes.
+pub fn to_canonical_vec<T: Serialize>(value: &T) -> Result<Vec<u8>, HandshakeError> {
+    let value = serde_json::to_value(value).map_err(|_| HandshakeError::BadJson)?;
+    canonicalize(&value)
+}
+
+/// Canonical bytes of an already-parsed JSON value.
+pub fn canonicalize(value: &Value) -> Result<Vec<u8>, HandshakeError> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out.into_bytes())
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), HandshakeError> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&format_number(n)?),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut members: Vec<(&String, &Value)> = map.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push('{');
+            for (i, (key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, item)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{0C}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn format_number(n: &Number) -> Result<String, HandshakeError> {
+    if let Some(u) = n.as_u64() {
+        if u > MAX_SAFE_INTEGER {
+            return Err(HandshakeError::NumberOutOfRange);
+        }
+        return Ok(u.to_string());
+    }
+    if let Some(i) = n.as_i64() {
+        if i.unsigned_abs() > MAX_SAFE_INTEGER {
+            return Err(HandshakeError::NumberOutOfRange);
+        }
+        return Ok(i.to_string());
+    }
+    match n.as_f64() {
+        Some(f) => format_f64(f),
+        None => Err(HandshakeError::NumberOutOfRange),
+    }
+}
+
+/// ECMAScript `Number.prototype.toString` for finite doubles.
+pub fn format_f64(f: f64) -> Result<String, HandshakeError> {
+    if !f.is_finite() {
+        return Err(HandshakeError::NumberOutOfRange);
+    }
+    if f == 0.0 {
+        // Covers -0 as well
+        return Ok("0".to_string());
+    }
+
+    // Shortest round-trip digits, e.g. "-1.2345e-7"
+    let sci = format!("{:e}", f);
+    let (sign, sci) = match sci.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", sci.as_str()),
+    };
+    let (mantissa, exp) = sci.split_once('e').expect("{:e} always has an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    // value = 0.<digits> × 10^n
+    let n = exp.parse::<i32>().expect("{:e} exponent is an integer") + 1;
+    let digits = break_tie_to_even(digits, n, f.abs());
+    let k = digits.len() as i32;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let e = n - 1;
+        let exp = if e >= 0 { format!("+{}", e) } else { e.to_string() };
+        if k == 1 {
+            format!("{}e{}", digits, exp)
+        } else {
+            format!("{}.{}e{}", &digits[..1], &digits[1..], exp)
+        }
+    };
+
+    Ok(format!("{}{}", sign, body))
+}
+
+/// ECMAScript picks the even candidate when two shortest digit strings are
+/// equally close to the exact value; Rust's formatter rounds such ties up.
+fn break_tie_to_even(digits: String, n: i32, x: f64) -> String {
+    let d: u64 = digits.parse().expect("at most 17 decimal digits");
+    if d.is_multiple_of(2) {
+        return digits;
+    }
+
+    // Midpoints below and above `d`, one digit longer: x = p × 10^q
+    let q = n - digits.len() as i32 - 1;
+    let candidate = if is_exactly(x, (d - 1) * 10 + 5, q) {
+        d - 1
+    } else if d % 10 != 9 && is_exactly(x, d * 10 + 5, q) {
+        d + 1
+    } else {
+        return digits;
+    };
+
+    candidate.to_string().trim_end_matches('0').to_string()
+}
+
+/// True if `p × 10^q` is exactly the double `x`.
+fn is_exactly(x: f64, p: u64, q: i32) -> bool {
+    // p × 10^q = p × 5^q × 2^q: dyadic with a 53-bit odd part, or not a double at all
+    let odd = if q >= 0 {
+        if q > 22 {
+            return false;
+        }
+        p as u128 * 5u128.pow(q as u32)
+    } else {
+        if -q > 27 {
+            return false;
+        }
+        let div = 5u128.pow((-q) as u32);
+        if !(p as u128).is_multiple_of(div) {
+            return false;
+        }
+        p as u128 / div
+    };
+    if odd >> odd.trailing_zeros() >= 1 << 53 {
+        return false;
+    }
+
+    // Exactly representable, so correct rounding makes equality exact
+    format!("{}e{}", p, q).parse::<f64>() == Ok(x)
+}
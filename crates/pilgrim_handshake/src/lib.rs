@@ -1,13 +1,26 @@
+use pilgrim_identity::{Identity, IdentityProof, Keypair, ProofScheme};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use version::{ProtocolVersion, VersionRange};
+
+pub use datum::{DatumKind, DatumValue, Decimal};
+pub use payload::{
+    ReceiptPayload, RunPayload, RunResult, StepLog, MIN_PAYLOAD_SCHEMA_VERSION,
+    PAYLOAD_SCHEMA_VERSION,
+};
+pub use schema::{FieldSpec, InputProblem, InputSchema, InputViolation};
 
 /// Handshake contract version (must be embedded into every envelope).
-pub const PROTOCOL_VERSION: &str = "amethyst-pilgrim-handshake/1";
+/// Written by the constructors; `verify` accepts any version in `version::SUPPORTED`.
+pub const PROTOCOL_VERSION: &str = "amethyst-pilgrim-handshake/2";
 
 /// Hash algorithms allowed by the contract.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum HashAlgo {
+    /// SHA-256 over RFC 8785 (JCS) bytes; under `/1`, over `serde_json` bytes.
     Sha256,
+    /// SHA-256 over deterministic CBOR bytes (see `cbor`).
+    Sha256Cbor,
 }
 
 /// Privacy tier is part of the contract (no inference).
@@ -43,7 +56,16 @@ impl Default for Constraints {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Datum {
     pub key: String,
-    pub value: String,
+    pub value: DatumValue,
+}
+
+impl Datum {
+    pub fn new(key: impl Into<String>, value: impl Into<DatumValue>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
 }
 
 /// Intent is the human-facing “ask” that becomes a deterministic run.
@@ -62,9 +84,24 @@ pub struct Intent {
     /// Contract constraints (bounded compute).
     pub constraints: Constraints,
     /// Nonce to prevent accidental replay collisions when intent_id reused.
+    /// Enforced by `replay::ReplayGuard`.
+    ///
+    /// At most `Intent::MAX_NONCE` (2^53 - 1): JCS checksums cannot seal
+    /// larger integers. Derive random nonces with `Intent::nonce_from_bits`.
     pub nonce: u64,
 }
 
+impl Intent {
+    /// Largest `nonce` every `HashAlgo` can seal.
+    pub const MAX_NONCE: u64 = jcs::MAX_SAFE_INTEGER;
+
+    /// A nonce from random bits (e.g. a random `u64`), folded into
+    /// `0..=MAX_NONCE`.
+    pub const fn nonce_from_bits(bits: u64) -> u64 {
+        bits & Self::MAX_NONCE
+    }
+}
+
 /// A checksum bound to the envelope’s canonical bytes.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Checksum {
@@ -72,12 +109,32 @@ pub struct Checksum {
     pub hex: String,
 }
 
+/// Signature algorithms allowed by the contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureAlgo {
+    Ed25519,
+}
+
+/// Who issued the envelope, and their signature over `seal_bytes()`.
+///
+/// The signed message is the SHA-256 hex of `seal_bytes()`, as an
+/// identity proof (see `pilgrim_identity::Keypair::prove`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureBlock {
+    pub signer_fingerprint: String,
+    pub algo: SignatureAlgo,
+    pub signature_hex: String,
+}
+
 /// A request envelope: versioned, canonical, and self-checking.
+/// Optionally signed: the checksum proves integrity, the signature proves origin.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RequestEnvelope {
     pub protocol: String,
     pub intent: Intent,
     pub checksum: Checksum,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SignatureBlock>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -91,6 +148,10 @@ pub enum RunStatus {
 }
 
 /// A minimal response contract (no engine logic here).
+///
+/// `/2` carries typed `payload`, `logs` and `violations`. `/1` predates
+/// them and carries free-form `payload_json` / `logs_json` instead; each
+/// version rejects the other's fields rather than leave them unsealed.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ResponseEnvelope {
     pub protocol: String,
@@ -98,9 +159,18 @@ pub struct ResponseEnvelope {
     pub status: RunStatus,
     /// Human-readable short status message (safe to show in UI).
     pub message: String,
-    /// Optional machine payload (engine will define exact schema later).
+    /// Run result + receipt, once the run has finished.
+    pub payload: Option<RunPayload>,
+    /// Per-step trace records, when the intent required logs.
+    pub logs: Option<Vec<StepLog>>,
+    /// Why the inputs were rejected (see `schema`); absent otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub violations: Option<Vec<InputViolation>>,
+    /// `/1` only: machine payload as an opaque JSON string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub payload_json: Option<String>,
-    /// Optional canonical logs reference (engine will define later).
+    /// `/1` only: logs as an opaque JSON string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub logs_json: Option<String>,
     pub checksum: Checksum,
 }
@@ -108,10 +178,55 @@ pub struct ResponseEnvelope {
 /// Contract errors (pure handshake layer).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum HandshakeError {
+    /// Not an `amethyst-pilgrim-handshake/<n>` protocol string.
     ProtocolMismatch { expected: String, got: String },
+    UnsupportedVersion {
+        got: ProtocolVersion,
+        supported: VersionRange,
+    },
+    NoCommonVersion {
+        offered: VersionRange,
+        supported: VersionRange,
+    },
     ChecksumMismatch,
     BadChecksumFormat,
     BadJson,
+    /// Not canonical CBOR (see `cbor`).
+    BadCbor,
+    /// A number outside the I-JSON range has no canonical (JCS) form.
+    NumberOutOfRange,
+    Unsigned,
+    UntrustedSigner { fingerprint: String },
+    BadSignature,
+    UnsupportedPayloadVersion { got: u16, supported: u16 },
+    /// Same (intent_id, nonce) or checksum already admitted.
+    Replayed { intent_id: String, nonce: u64 },
+    OutsideReplayWindow { created_unix_ms: u64, now_unix_ms: u64 },
+    /// Replay store at capacity with live entries; refusing rather than forgetting.
+    ReplayStoreFull,
+    /// Constraints JSON parsed, but one or more fields are unusable.
+    InvalidConstraints(Vec<FieldError>),
+    /// Chunk layout does not add up (see `chunked`).
+    BadManifest,
+    /// Chunk not part of the manifest's transfer.
+    BadChunk { index: u32 },
+}
+
+/// One rejected field in bridge-supplied JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub problem: FieldProblem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldProblem {
+    Missing,
+    /// Present but not the expected JSON type (e.g. a string for a number).
+    WrongType { expected: &'static str },
+    OutOfRange { min: u64, max: u64 },
+    UnknownVariant { got: String },
+    UnknownField,
 }
 
 fn sha256_hex(bytes: &[u8]) -> String {
@@ -121,103 +236,283 @@ fn sha256_hex(bytes: &[u8]) -> String {
     hex::encode(out)
 }
 
-/// Canonical bytes for deterministic hashing:
-/// - JSON serialization of the struct (field order is deterministic for structs)
-/// - No maps used in this contract (to avoid key-order ambiguity)
-fn canonical_json_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, HandshakeError> {
-    serde_json::to_vec(value).map_err(|_| HandshakeError::BadJson)
+/// Canonical bytes for deterministic hashing, per checksum algorithm.
+/// Independent of struct field order and reproducible from any language.
+fn canonical_bytes<T: Serialize>(algo: HashAlgo, value: &T) -> Result<Vec<u8>, HandshakeError> {
+    match algo {
+        HashAlgo::Sha256 => jcs::to_canonical_vec(value),
+        HashAlgo::Sha256Cbor => cbor::to_canonical_vec(value),
+    }
+}
+
+/// Bytes an envelope checksum covers under `protocol`.
+///
+/// `/1` predates canonical encodings: it hashes plain `serde_json` output
+/// (struct field order) and knows only `Sha256`. Later versions hash
+/// `canonical_bytes`.
+fn sealed_bytes<T: Serialize>(
+    protocol: &str,
+    algo: HashAlgo,
+    value: &T,
+) -> Result<Vec<u8>, HandshakeError> {
+    match (version::check_protocol(protocol)?, algo) {
+        (ProtocolVersion::V1, HashAlgo::Sha256) => {
+            serde_json::to_vec(value).map_err(|_| HandshakeError::BadJson)
+        }
+        (ProtocolVersion::V1, _) => Err(HandshakeError::BadChecksumFormat),
+        _ => canonical_bytes(algo, value),
+    }
 }
 
 impl RequestEnvelope {
     /// Create a request envelope with correct protocol + checksum.
-    pub fn new(intent: Intent) -> Self {
+    ///
+    /// Fails with `NumberOutOfRange` if the intent has no canonical form
+    /// (e.g. a `nonce` above `Intent::MAX_NONCE` under JCS).
+    pub fn new(intent: Intent) -> Result<Self, HandshakeError> {
+        Self::new_with_algo(intent, HashAlgo::Sha256)
+    }
+
+    /// Create a request envelope sealed with `algo`.
+    pub fn new_with_algo(intent: Intent, algo: HashAlgo) -> Result<Self, HandshakeError> {
         let mut env = Self {
             protocol: PROTOCOL_VERSION.to_string(),
             intent,
             checksum: Checksum {
-                algo: HashAlgo::Sha256,
+                algo,
                 hex: String::new(),
             },
+            signature: None,
         };
-        env.checksum.hex = env.compute_checksum_hex();
-        env
+        env.checksum.hex = env.compute_checksum_hex()?;
+        Ok(env)
     }
 
-    /// Compute checksum over canonical bytes of (protocol + intent).
-    fn compute_checksum_hex(&self) -> String {
-        // Hash only stable fields (exclude checksum itself to avoid recursion)
-        let to_hash = RequestToHash {
-            protocol: self.protocol.clone(),
-            intent: self.intent.clone(),
+    /// Create a request envelope signed by `signer`.
+    pub fn new_signed(intent: Intent, signer: &Keypair) -> Result<Self, HandshakeError> {
+        let mut env = Self::new(intent)?;
+        env.sign(signer)?;
+        Ok(env)
+    }
+
+    /// Sign (or re-sign) the envelope's sealed bytes.
+    pub fn sign(&mut self, signer: &Keypair) -> Result<(), HandshakeError> {
+        let proof = signer.prove(sha256_hex(&self.seal_bytes()?));
+        self.signature = Some(SignatureBlock {
+            signer_fingerprint: proof.pubkey_fingerprint,
+            algo: SignatureAlgo::Ed25519,
+            signature_hex: proof.proof_hash,
+        });
+        Ok(())
+    }
+
+    /// Verify protocol + checksum, then require a valid signature from one of
+    /// `trusted_keys`. Returns the matching signer.
+    pub fn verify_signed<'a>(
+        &self,
+        trusted_keys: &'a [Identity],
+    ) -> Result<&'a Identity, HandshakeError> {
+        self.verify()?;
+
+        let block = self.signature.as_ref().ok_or(HandshakeError::Unsigned)?;
+        let signer = trusted_keys
+            .iter()
+            .find(|id| id.pubkey_fingerprint == block.signer_fingerprint)
+            .ok_or_else(|| HandshakeError::UntrustedSigner {
+                fingerprint: block.signer_fingerprint.clone(),
+            })?;
+
+        let proof = IdentityProof {
+            subject_id: signer.subject_id.clone(),
+            pubkey_fingerprint: block.signer_fingerprint.clone(),
+            message_hash: sha256_hex(&self.seal_bytes()?),
+            scheme: match block.algo {
+                SignatureAlgo::Ed25519 => ProofScheme::Ed25519,
+            },
+            proof_hash: block.signature_hex.clone(),
         };
-        let bytes = canonical_json_bytes(&to_hash).unwrap_or_else(|_| Vec::new());
-        sha256_hex(&bytes)
+        signer
+            .verify(&proof)
+            .map_err(|_| HandshakeError::BadSignature)?;
+
+        Ok(signer)
     }
 
-    /// Verify protocol + checksum.
+    /// Compute checksum over canonical bytes of (protocol + intent).
+    fn compute_checksum_hex(&self) -> Result<String, HandshakeError> {
+        Ok(sha256_hex(&self.seal_bytes()?))
+    }
+
+    /// Verify protocol (any supported version) + checksum.
     pub fn verify(&self) -> Result<(), HandshakeError> {
-        if self.protocol != PROTOCOL_VERSION {
-            return Err(HandshakeError::ProtocolMismatch {
-                expected: PROTOCOL_VERSION.to_string(),
-                got: self.protocol.clone(),
-            });
+        version::check_protocol(&self.protocol)?;
+        let expected = self.compute_checksum_hex()?;
+        if expected != self.checksum.hex {
+            return Err(HandshakeError::ChecksumMismatch);
         }
-        match self.checksum.algo {
-            HashAlgo::Sha256 => {
-                let expected = self.compute_checksum_hex();
-                if expected != self.checksum.hex {
-                    return Err(HandshakeError::ChecksumMismatch);
-                }
-                Ok(())
+        Ok(())
+    }
+
+    /// Canonical CBOR of the whole envelope.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, HandshakeError> {
+        cbor::to_canonical_vec(self)
+    }
+
+    /// Decode from canonical CBOR (does not verify).
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, HandshakeError> {
+        serde_json::from_value(cbor::decode(bytes)?).map_err(|_| HandshakeError::BadCbor)
+    }
+
+    /// Verify, then check the inputs against `schema`.
+    ///
+    /// Integrity failures are errors (there is no trustworthy intent to
+    /// answer). Schema violations are an answer: a `Rejected` response,
+    /// sealed like this request, listing every violation.
+    pub fn accept(&self, schema: &InputSchema) -> Result<ResponseEnvelope, HandshakeError> {
+        self.verify()?;
+        let intent_id = self.intent.intent_id.clone();
+        let algo = self.checksum.algo;
+        match schema.validate(&self.intent) {
+            Ok(()) => ResponseEnvelope::new_with_algo(
+                intent_id,
+                RunStatus::Accepted,
+                "Accepted.".to_string(),
+                algo,
+            ),
+            Err(violations) => {
+                let message = format!(
+                    "Rejected: {}.",
+                    violations
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                );
+                ResponseEnvelope::new_with_algo(intent_id, RunStatus::Rejected, message, algo)?
+                    .with_violations(violations)
             }
         }
     }
 }
 
 impl ResponseEnvelope {
-    pub fn new(intent_id: String, status: RunStatus, message: String) -> Self {
+    /// Create a response envelope with correct protocol + checksum.
+    ///
+    /// This and every `with_*` call reseal the envelope, failing with
+    /// `NumberOutOfRange` if it has no canonical form.
+    pub fn new(
+        intent_id: String,
+        status: RunStatus,
+        message: String,
+    ) -> Result<Self, HandshakeError> {
+        Self::new_with_algo(intent_id, status, message, HashAlgo::Sha256)
+    }
+
+    /// Create a response envelope sealed with `algo`.
+    pub fn new_with_algo(
+        intent_id: String,
+        status: RunStatus,
+        message: String,
+        algo: HashAlgo,
+    ) -> Result<Self, HandshakeError> {
         let mut env = Self {
             protocol: PROTOCOL_VERSION.to_string(),
             intent_id,
             status,
             message,
+            payload: None,
+            logs: None,
+            violations: None,
             payload_json: None,
             logs_json: None,
             checksum: Checksum {
-                algo: HashAlgo::Sha256,
+                algo,
                 hex: String::new(),
             },
         };
-        env.checksum.hex = env.compute_checksum_hex();
-        env
-    }
-
-    fn compute_checksum_hex(&self) -> String {
-        let to_hash = ResponseToHash {
-            protocol: self.protocol.clone(),
-            intent_id: self.intent_id.clone(),
-            status: self.status.clone(),
-            message: self.message.clone(),
-            payload_json: self.payload_json.clone(),
-            logs_json: self.logs_json.clone(),
+        env.checksum.hex = env.compute_checksum_hex()?;
+        Ok(env)
+    }
+
+    /// Attach the run payload and reseal.
+    pub fn with_payload(mut self, payload: RunPayload) -> Result<Self, HandshakeError> {
+        self.payload = Some(payload);
+        self.checksum.hex = self.compute_checksum_hex()?;
+        Ok(self)
+    }
+
+    /// Attach step logs and reseal.
+    pub fn with_logs(mut self, logs: Vec<StepLog>) -> Result<Self, HandshakeError> {
+        self.logs = Some(logs);
+        self.checksum.hex = self.compute_checksum_hex()?;
+        Ok(self)
+    }
+
+    /// Attach input violations and reseal.
+    pub fn with_violations(
+        mut self,
+        violations: Vec<InputViolation>,
+    ) -> Result<Self, HandshakeError> {
+        self.violations = Some(violations);
+        self.checksum.hex = self.compute_checksum_hex()?;
+        Ok(self)
+    }
+
+    fn compute_checksum_hex(&self) -> Result<String, HandshakeError> {
+        let typed = self.payload.is_some() || self.logs.is_some() || self.violations.is_some();
+        let untyped = self.payload_json.is_some() || self.logs_json.is_some();
+        let bytes = if version::check_protocol(&self.protocol)? == ProtocolVersion::V1 {
+            if typed {
+                return Err(HandshakeError::BadJson);
+            }
+            let to_hash = ResponseToHashV1 {
+                protocol: &self.protocol,
+                intent_id: &self.intent_id,
+                status: &self.status,
+                message: &self.message,
+                payload_json: self.payload_json.as_deref(),
+                logs_json: self.logs_json.as_deref(),
+            };
+            sealed_bytes(&self.protocol, self.checksum.algo, &to_hash)?
+        } else {
+            if untyped {
+                return Err(HandshakeError::BadJson);
+            }
+            let to_hash = ResponseToHash {
+                protocol: self.protocol.clone(),
+                intent_id: self.intent_id.clone(),
+                status: self.status.clone(),
+                message: self.message.clone(),
+                payload: self.payload.clone(),
+                logs: self.logs.clone(),
+                violations: self.violations.clone(),
+            };
+            sealed_bytes(&self.protocol, self.checksum.algo, &to_hash)?
         };
-        let bytes = canonical_json_bytes(&to_hash).unwrap_or_else(|_| Vec::new());
-        sha256_hex(&bytes)
+        Ok(sha256_hex(&bytes))
     }
 
     pub fn verify(&self) -> Result<(), HandshakeError> {
-        if self.protocol != PROTOCOL_VERSION {
-            return Err(HandshakeError::ProtocolMismatch {
-                expected: PROTOCOL_VERSION.to_string(),
-                got: self.protocol.clone(),
-            });
-        }
-        let expected = self.compute_checksum_hex();
+        version::check_protocol(&self.protocol)?;
+        let expected = self.compute_checksum_hex()?;
         if expected != self.checksum.hex {
             return Err(HandshakeError::ChecksumMismatch);
         }
+        if let Some(payload) = &self.payload {
+            payload.check_schema()?;
+        }
         Ok(())
     }
+
+    /// Canonical CBOR of the whole envelope.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, HandshakeError> {
+        cbor::to_canonical_vec(self)
+    }
+
+    /// Decode from canonical CBOR (does not verify).
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, HandshakeError> {
+        serde_json::from_value(cbor::decode(bytes)?).map_err(|_| HandshakeError::BadCbor)
+    }
 }
 
 /// Internal structs: explicitly define what gets hashed (excludes checksum fields).
@@ -233,8 +528,30 @@ struct ResponseToHash {
     intent_id: String,
     status: RunStatus,
     message: String,
-    payload_json: Option<String>,
-    logs_json: Option<String>,
+    payload: Option<RunPayload>,
+    logs: Option<Vec<StepLog>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    violations: Option<Vec<InputViolation>>,
+}
+
+/// What `/1` responses hash, in `/1` field order.
+#[derive(Serialize)]
+struct ResponseToHashV1<'a> {
+    protocol: &'a str,
+    intent_id: &'a str,
+    status: &'a RunStatus,
+    message: &'a str,
+    payload_json: Option<&'a str>,
+    logs_json: Option<&'a str>,
 }
 
 mod bridge_api;
+pub mod cbor;
+pub mod chunked;
+pub mod datum;
+pub mod jcs;
+pub mod merkle;
+pub mod payload;
+pub mod replay;
+pub mod schema;
+pub mod version;
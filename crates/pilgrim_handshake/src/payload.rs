@@ -0,0 +1,98 @@
+//! Typed response payloads.
+//!
+//! Structural replacement for the old opaque `payload_json` / `logs_json`
+//! strings: embedded in `ResponseEnvelope` as-is and covered by its
+//! checksum, so clients decode results in one pass.
+//!
+//! Field names mirror `pilgrim_core::receipt::Receipt` and
+//! `pilgrim_core::trace::TraceStep`; this crate does not depend on the core.
+
+use crate::{Datum, HandshakeError};
+use serde::{Deserialize, Serialize};
+
+/// `RunPayload` schema version (bump on any field change).
+pub const PAYLOAD_SCHEMA_VERSION: u16 = 3;
+
+/// Oldest `RunPayload` schema still read (`/1` payloads, before
+/// `merkle_root`).
+pub const MIN_PAYLOAD_SCHEMA_VERSION: u16 = 1;
+
+/// Machine-readable result of a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunPayload {
+    pub schema_version: u16,
+    pub result: RunResult,
+    pub receipt: ReceiptPayload,
+}
+
+/// What the run produced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunResult {
+    /// Cartridge that executed the run.
+    pub cartridge_id: String,
+    /// Named outputs, in emission order.
+    pub outputs: Vec<Datum>,
+}
+
+/// Receipt fields, as sealed by the engine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptPayload {
+    pub run_id: String,
+    pub intent_statement: String,
+    pub final_trace_hash: String,
+    pub steps: u64,
+    /// Hex Merkle root over the trace steps (since schema 2; empty when
+    /// read from a schema 1 payload, and then left off the wire so its
+    /// checksum still holds).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub merkle_root: String,
+    /// Whether the Merkle leaves are salted (since schema 3): a salted root
+    /// only re-derives with the runner's secret salt key.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub salted: bool,
+}
+
+/// One trace step, for clients that asked for logs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepLog {
+    pub index: u64,
+    pub name: String,
+    pub checksum_hex: String,
+    pub len: u64,
+}
+
+impl RunPayload {
+    pub fn new(result: RunResult, receipt: ReceiptPayload) -> Self {
+        Self {
+            schema_version: PAYLOAD_SCHEMA_VERSION,
+            result,
+            receipt,
+        }
+    }
+
+    /// Final trace hash (from the receipt).
+    pub fn trace_hash(&self) -> &str {
+        &self.receipt.final_trace_hash
+    }
+
+    /// Merkle root over the trace steps (from the receipt).
+    pub fn merkle_root(&self) -> &str {
+        &self.receipt.merkle_root
+    }
+
+    /// Number of executed steps (from the receipt).
+    pub fn step_count(&self) -> u64 {
+        self.receipt.steps
+    }
+
+    /// Reject payloads written against a schema this build does not know.
+    pub fn check_schema(&self) -> Result<(), HandshakeError> {
+        if !(MIN_PAYLOAD_SCHEMA_VERSION..=PAYLOAD_SCHEMA_VERSION).contains(&self.schema_version) {
+            return Err(HandshakeError::UnsupportedPayloadVersion {
+                got: self.schema_version,
+                supported: PAYLOAD_SCHEMA_VERSION,
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+//! Replay protection for request envelopes.
+//!
+//! A `ReplayGuard` admits each request at most once, and only when it is
+//! signed by a trusted key: the checksum alone is unkeyed, so anyone could
+//! mint a fresh nonce and reseal. It remembers
+//! `(intent_id, nonce)` and the envelope checksum for every admitted
+//! request whose `created_unix_ms` is still inside the window:
+//! - older than `max_age_ms`, or further ahead than `max_skew_ms`: rejected
+//! - same `(intent_id, nonce)` or same checksum as a remembered request: rejected
+//!
+//! Entries leave the store only once they fall out of the window, when a
+//! replay would be rejected by the window check anyway. A full store
+//! refuses new requests rather than forgetting live ones (fail closed).
+//!
+//! The caller supplies `now_unix_ms`; the guard never reads a clock. A
+//! `now` earlier than one already seen counts as that one, so a clock
+//! stepping back cannot reopen the window for forgotten requests.
+
+use crate::{HandshakeError, RequestEnvelope};
+use pilgrim_identity::Identity;
+use std::collections::{BTreeMap, HashSet};
+
+/// Replay window and store bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayConfig {
+    /// How far in the past `created_unix_ms` may be.
+    pub max_age_ms: u64,
+    /// How far in the future `created_unix_ms` may be (client clock skew).
+    pub max_skew_ms: u64,
+    /// Most requests remembered at once.
+    pub capacity: usize,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            max_age_ms: 5 * 60 * 1000,
+            max_skew_ms: 30 * 1000,
+            capacity: 100_000,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Seen {
+    intent_id: String,
+    nonce: u64,
+    checksum: String,
+}
+
+/// Bounded memory of admitted requests.
+#[derive(Debug)]
+pub struct ReplayGuard {
+    config: ReplayConfig,
+    /// Keyed by (created_unix_ms, admission order) for expiry.
+    by_time: BTreeMap<(u64, u64), Seen>,
+    ids: HashSet<(String, u64)>,
+    checksums: HashSet<String>,
+    admitted: u64,
+    /// Latest `now_unix_ms` passed to `admit`.
+    latest_now_ms: u64,
+}
+
+impl ReplayGuard {
+    pub fn new(config: ReplayConfig) -> Self {
+        Self {
+            config,
+            by_time: BTreeMap::new(),
+            ids: HashSet::new(),
+            checksums: HashSet::new(),
+            admitted: 0,
+            latest_now_ms: 0,
+        }
+    }
+
+    pub fn config(&self) -> &ReplayConfig {
+        &self.config
+    }
+
+    /// Requests currently remembered.
+    pub fn len(&self) -> usize {
+        self.by_time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_time.is_empty()
+    }
+
+    /// Verify `env` and its signature against `trusted_keys`, then admit it
+    /// exactly once. Returns the signer.
+    ///
+    /// Nothing is remembered unless every check passes.
+    pub fn admit<'a>(
+        &mut self,
+        env: &RequestEnvelope,
+        trusted_keys: &'a [Identity],
+        now_unix_ms: u64,
+    ) -> Result<&'a Identity, HandshakeError> {
+        let signer = env.verify_signed(trusted_keys)?;
+
+        let now_unix_ms = now_unix_ms.max(self.latest_now_ms);
+        self.latest_now_ms = now_unix_ms;
+        let created = env.intent.created_unix_ms;
+        let oldest = now_unix_ms.saturating_sub(self.config.max_age_ms);
+        let newest = now_unix_ms.saturating_add(self.config.max_skew_ms);
+        if created < oldest || created > newest {
+            return Err(HandshakeError::OutsideReplayWindow {
+                created_unix_ms: created,
+                now_unix_ms,
+            });
+        }
+
+        self.expire(oldest);
+
+        let id = (env.intent.intent_id.clone(), env.intent.nonce);
+        if self.ids.contains(&id) || self.checksums.contains(&env.checksum.hex) {
+            return Err(HandshakeError::Replayed {
+                intent_id: id.0,
+                nonce: id.1,
+            });
+        }
+        if self.by_time.len() >= self.config.capacity {
+            return Err(HandshakeError::ReplayStoreFull);
+        }
+
+        self.ids.insert(id);
+        self.checksums.insert(env.checksum.hex.clone());
+        self.by_time.insert(
+            (created, self.admitted),
+            Seen {
+                intent_id: env.intent.intent_id.clone(),
+                nonce: env.intent.nonce,
+                checksum: env.checksum.hex.clone(),
+            },
+        );
+        self.admitted += 1;
+        Ok(signer)
+    }
+
+    /// Forget everything created before `oldest`.
+    fn expire(&mut self, oldest: u64) {
+        let live = self.by_time.split_off(&(oldest, 0));
+        for seen in std::mem::replace(&mut self.by_time, live).into_values() {
+            self.ids.remove(&(seen.intent_id, seen.nonce));
+            self.checksums.remove(&seen.checksum);
+        }
+    }
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        Self::new(ReplayConfig::default())
+    }
+}
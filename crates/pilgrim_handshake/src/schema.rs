@@ -0,0 +1,259 @@
+//! Input schemas: which `Datum` keys a run expects.
+//!
+//! A schema lists every accepted key with its type, whether it is
+//! required, an optional inclusive range and how often it may repeat.
+//! Keys not in the schema are violations (a typo is not an optional
+//! input), and a missing required key is never filled in (SAFE_002).
+//!
+//! `RequestEnvelope::accept` runs the check and answers with a sealed
+//! `Accepted` or `Rejected` response; the rejection lists every
+//! violation, not just the first.
+
+use crate::datum::DatumKind;
+use crate::{Datum, DatumValue, Intent};
+use amethyst_invariants::{Enforced, EnforcementPoint};
+use serde::{Deserialize, Serialize};
+
+// Missing inputs are rejected rather than defaulted
+const COMPLETE: EnforcementPoint = EnforcementPoint::new(
+    "SAFE_002",
+    "pilgrim_handshake::schema::InputSchema::validate",
+);
+// Undeclared inputs are rejected rather than quietly passed along
+const DECLARED: EnforcementPoint = EnforcementPoint::new(
+    "TRUTH_002",
+    "pilgrim_handshake::schema::InputSchema::validate",
+);
+
+/// System invariants upheld by input validation.
+pub const ENFORCEMENT: &[EnforcementPoint] = &[COMPLETE, DECLARED];
+
+/// Inclusive bounds.
+///
+/// Applies to the value of `int` and `decimal` inputs (compared exactly,
+/// whatever the scale) and to the length of `text` (chars), `bytes` and
+/// `list` inputs. Ignored for `bool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub min: i64,
+    pub max: i64,
+}
+
+/// One expected input key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSpec {
+    pub key: String,
+    pub kind: DatumKind,
+    pub required: bool,
+    pub range: Option<Bounds>,
+    /// How many `Datum`s may carry this key.
+    pub max_count: u32,
+}
+
+impl FieldSpec {
+    pub fn required(key: impl Into<String>, kind: DatumKind) -> Self {
+        Self {
+            key: key.into(),
+            kind,
+            required: true,
+            range: None,
+            max_count: 1,
+        }
+    }
+
+    pub fn optional(key: impl Into<String>, kind: DatumKind) -> Self {
+        Self {
+            required: false,
+            ..Self::required(key, kind)
+        }
+    }
+
+    pub fn with_range(mut self, min: i64, max: i64) -> Self {
+        self.range = Some(Bounds { min, max });
+        self
+    }
+
+    pub fn with_max_count(mut self, max_count: u32) -> Self {
+        self.max_count = max_count;
+        self
+    }
+}
+
+/// The inputs a run accepts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputSchema {
+    pub fields: Vec<FieldSpec>,
+    /// Upper bound on `Intent::inputs.len()`.
+    pub max_inputs: Option<u32>,
+}
+
+/// One way an intent's inputs break the schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputViolation {
+    /// Offending key (empty for `TooManyInputs`).
+    pub key: String,
+    pub problem: InputProblem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InputProblem {
+    /// Required key absent.
+    Missing,
+    /// Key not declared by the schema.
+    Unknown,
+    WrongType {
+        expected: DatumKind,
+        got: DatumKind,
+    },
+    OutOfRange {
+        min: i64,
+        max: i64,
+    },
+    /// Key repeated more than `max_count` times.
+    TooMany {
+        max: u32,
+        got: u32,
+    },
+    TooManyInputs {
+        max: u32,
+        got: u32,
+    },
+}
+
+impl Enforced for InputViolation {
+    fn enforcement_point(&self) -> &'static EnforcementPoint {
+        match self.problem {
+            InputProblem::Unknown => &DECLARED,
+            _ => &COMPLETE,
+        }
+    }
+}
+
+impl std::fmt::Display for InputViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.problem {
+            InputProblem::Missing => write!(f, "{}: missing", self.key),
+            InputProblem::Unknown => write!(f, "{}: not in schema", self.key),
+            InputProblem::WrongType { expected, got } => write!(
+                f,
+                "{}: expected {}, got {}",
+                self.key,
+                expected.name(),
+                got.name()
+            ),
+            InputProblem::OutOfRange { min, max } => {
+                write!(f, "{}: outside {}..={}", self.key, min, max)
+            }
+            InputProblem::TooMany { max, got } => {
+                write!(f, "{}: {} values, at most {}", self.key, got, max)
+            }
+            InputProblem::TooManyInputs { max, got } => {
+                write!(f, "{} inputs, at most {}", got, max)
+            }
+        }
+    }
+}
+
+impl InputSchema {
+    pub fn new(fields: Vec<FieldSpec>) -> Self {
+        Self {
+            fields,
+            max_inputs: None,
+        }
+    }
+
+    pub fn with_max_inputs(mut self, max_inputs: u32) -> Self {
+        self.max_inputs = Some(max_inputs);
+        self
+    }
+
+    /// Check `intent.inputs`, collecting every violation.
+    ///
+    /// Order: input count, then schema fields in declaration order, then
+    /// unknown keys in input order.
+    pub fn validate(&self, intent: &Intent) -> Result<(), Vec<InputViolation>> {
+        let inputs = &intent.inputs;
+        let mut violations = Vec::new();
+        let violation = |key: &str, problem| InputViolation {
+            key: key.to_string(),
+            problem,
+        };
+
+        let total = u32::try_from(inputs.len()).unwrap_or(u32::MAX);
+        if let Some(max) = self.max_inputs.filter(|max| total > *max) {
+            violations.push(violation(
+                "",
+                InputProblem::TooManyInputs { max, got: total },
+            ));
+        }
+
+        for spec in &self.fields {
+            let matching: Vec<&Datum> = inputs.iter().filter(|d| d.key == spec.key).collect();
+            if matching.is_empty() && spec.required {
+                violations.push(violation(&spec.key, InputProblem::Missing));
+            }
+            let count = u32::try_from(matching.len()).unwrap_or(u32::MAX);
+            if count > spec.max_count {
+                violations.push(violation(
+                    &spec.key,
+                    InputProblem::TooMany {
+                        max: spec.max_count,
+                        got: count,
+                    },
+                ));
+            }
+            for datum in matching {
+                if let Some(problem) = spec.check(&datum.value) {
+                    violations.push(violation(&spec.key, problem));
+                }
+            }
+        }
+
+        for datum in inputs {
+            if !self.fields.iter().any(|spec| spec.key == datum.key) {
+                violations.push(violation(&datum.key, InputProblem::Unknown));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+impl FieldSpec {
+    fn check(&self, value: &DatumValue) -> Option<InputProblem> {
+        let got = value.kind();
+        if got != self.kind {
+            return Some(InputProblem::WrongType {
+                expected: self.kind,
+                got,
+            });
+        }
+        let Bounds { min, max } = self.range?;
+        let in_range = match value {
+            DatumValue::Integer(i) => (min..=max).contains(i),
+            DatumValue::Decimal(d) => {
+                // units × 10^-scale vs bound × 10^0, both lifted to the same
+                // scale; a scale too large to lift is out of range
+                let units = d.units as i128;
+                10i128.checked_pow(d.scale as u32).is_some_and(|factor| {
+                    let lift = |bound: i64| (bound as i128).checked_mul(factor);
+                    lift(min).is_some_and(|lo| units >= lo)
+                        && lift(max).is_some_and(|hi| units <= hi)
+                })
+            }
+            DatumValue::Text(s) => within(s.chars().count(), min, max),
+            DatumValue::Bytes(b) => within(b.len(), min, max),
+            DatumValue::List(items) => within(items.len(), min, max),
+            DatumValue::Bool(_) => true,
+        };
+        (!in_range).then_some(InputProblem::OutOfRange { min, max })
+    }
+}
+
+fn within(len: usize, min: i64, max: i64) -> bool {
+    i64::try_from(len).is_ok_and(|len| (min..=max).contains(&len))
+}
@@ -0,0 +1,369 @@
+//! Protocol versions and negotiation.
+//!
+//! Every envelope names its protocol as `amethyst-pilgrim-handshake/<n>`.
+//! Before exchanging envelopes, peers agree on one version:
+//! - the client sends a `ProtocolOffer` with the range it speaks
+//! - the server answers with a `ProtocolAccept` naming the chosen version
+//! - the client `confirm`s the choice against its own range
+//!
+//! Rules:
+//! - upgrade: the highest version both sides speak is always chosen
+//! - downgrade: never below the local `min`; a peer cannot talk us back
+//!   into a version we dropped
+//! - no common version is an error, never a silent fallback
+//!
+//! Each supported version has its own decode path in `REGISTRY`. A decoder
+//! verifies the envelope in its own wire form, then lifts it into the
+//! current in-memory types, so old clients keep working after an upgrade.
+
+use crate::{HandshakeError, RequestEnvelope, ResponseEnvelope, PROTOCOL_VERSION};
+use serde::{Deserialize, Serialize};
+
+/// Protocol family prefix shared by all versions.
+pub const PROTOCOL_FAMILY: &str = "amethyst-pilgrim-handshake";
+
+/// A protocol version number, serialized as `amethyst-pilgrim-handshake/<n>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProtocolVersion(pub u16);
+
+impl ProtocolVersion {
+    /// Checksums over plain `serde_json` bytes, `Sha256` only.
+    pub const V1: Self = Self(1);
+    /// Checksums over canonical bytes (JCS or CBOR, see `HashAlgo`).
+    pub const V2: Self = Self(2);
+
+    /// Version written by `RequestEnvelope::new` / `ResponseEnvelope::new`.
+    pub const CURRENT: Self = Self::V2;
+
+    /// Parse `amethyst-pilgrim-handshake/<n>`.
+    pub fn parse(s: &str) -> Result<Self, HandshakeError> {
+        s.strip_prefix(PROTOCOL_FAMILY)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .filter(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|n| n.parse::<u16>().ok())
+            .map(Self)
+            .ok_or_else(|| HandshakeError::ProtocolMismatch {
+                expected: PROTOCOL_VERSION.to_string(),
+                got: s.to_string(),
+            })
+    }
+}
+
+impl std::fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", PROTOCOL_FAMILY, self.0)
+    }
+}
+
+impl From<ProtocolVersion> for String {
+    fn from(v: ProtocolVersion) -> Self {
+        v.to_string()
+    }
+}
+
+impl TryFrom<String> for ProtocolVersion {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s).map_err(|_| format!("not a {} version: {}", PROTOCOL_FAMILY, s))
+    }
+}
+
+/// Inclusive range of protocol versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRange {
+    pub min: ProtocolVersion,
+    pub max: ProtocolVersion,
+}
+
+impl VersionRange {
+    pub const fn new(min: ProtocolVersion, max: ProtocolVersion) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, v: ProtocolVersion) -> bool {
+        self.min <= v && v <= self.max
+    }
+
+    /// Versions in both ranges, if any.
+    pub fn intersect(&self, other: &VersionRange) -> Option<VersionRange> {
+        let common = VersionRange::new(self.min.max(other.min), self.max.min(other.max));
+        (common.min <= common.max).then_some(common)
+    }
+}
+
+/// Versions this build speaks. Must match the entries in `REGISTRY`.
+pub const SUPPORTED: VersionRange = VersionRange::new(ProtocolVersion::V1, ProtocolVersion::V2);
+
+/// Decode path for one protocol version.
+pub struct VersionEntry {
+    pub version: ProtocolVersion,
+    pub decode_request: fn(&[u8]) -> Result<RequestEnvelope, HandshakeError>,
+    pub decode_response: fn(&[u8]) -> Result<ResponseEnvelope, HandshakeError>,
+}
+
+/// Per-version decoders, oldest first.
+pub const REGISTRY: &[VersionEntry] = &[
+    VersionEntry {
+        version: ProtocolVersion::V1,
+        decode_request: v1::decode_request,
+        decode_response: v1::decode_response,
+    },
+    VersionEntry {
+        version: ProtocolVersion::V2,
+        decode_request: v2::decode_request,
+        decode_response: v2::decode_response,
+    },
+];
+
+/// Registered decode path for `version`, if this build supports it.
+pub fn entry(version: ProtocolVersion) -> Option<&'static VersionEntry> {
+    if !SUPPORTED.contains(version) {
+        return None;
+    }
+    REGISTRY.iter().find(|e| e.version == version)
+}
+
+/// Check that an envelope's protocol string names a supported version.
+pub fn check_protocol(protocol: &str) -> Result<ProtocolVersion, HandshakeError> {
+    lookup(protocol).map(|e| e.version)
+}
+
+fn lookup(protocol: &str) -> Result<&'static VersionEntry, HandshakeError> {
+    let version = ProtocolVersion::parse(protocol)?;
+    entry(version).ok_or(HandshakeError::UnsupportedVersion {
+        got: version,
+        supported: SUPPORTED,
+    })
+}
+
+/// Decode and verify a request envelope of any supported version.
+pub fn decode_request(bytes: &[u8]) -> Result<RequestEnvelope, HandshakeError> {
+    (entry_for(bytes)?.decode_request)(bytes)
+}
+
+/// Decode and verify a response envelope of any supported version.
+pub fn decode_response(bytes: &[u8]) -> Result<ResponseEnvelope, HandshakeError> {
+    (entry_for(bytes)?.decode_response)(bytes)
+}
+
+/// Only the `protocol` field is read before dispatching.
+#[derive(Deserialize)]
+struct ProtocolPeek {
+    protocol: String,
+}
+
+fn entry_for(bytes: &[u8]) -> Result<&'static VersionEntry, HandshakeError> {
+    let peek: ProtocolPeek = serde_json::from_slice(bytes).map_err(|_| HandshakeError::BadJson)?;
+    lookup(&peek.protocol)
+}
+
+/// Client → server: the versions the client speaks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolOffer {
+    pub supported: VersionRange,
+}
+
+/// Server → client: the version both sides will use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolAccept {
+    pub selected: ProtocolVersion,
+}
+
+impl ProtocolOffer {
+    /// Offer everything this build speaks.
+    pub fn new() -> Self {
+        Self {
+            supported: SUPPORTED,
+        }
+    }
+
+    /// Server side: pick the highest version in both `self` and `local`.
+    pub fn negotiate(&self, local: &VersionRange) -> Result<ProtocolAccept, HandshakeError> {
+        let common = local
+            .intersect(&self.supported)
+            .ok_or(HandshakeError::NoCommonVersion {
+                offered: self.supported,
+                supported: *local,
+            })?;
+        Ok(ProtocolAccept {
+            selected: common.max,
+        })
+    }
+}
+
+impl Default for ProtocolOffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProtocolAccept {
+    /// Client side: refuse a selection outside what we offered.
+    pub fn confirm(&self, local: &VersionRange) -> Result<ProtocolVersion, HandshakeError> {
+        if !local.contains(self.selected) {
+            return Err(HandshakeError::UnsupportedVersion {
+                got: self.selected,
+                supported: *local,
+            });
+        }
+        Ok(self.selected)
+    }
+}
+
+/// `amethyst-pilgrim-handshake/1`, decoded from its own wire layout.
+///
+/// Inputs were plain strings, payload and logs opaque JSON strings, and the
+/// checksum was SHA-256 over `serde_json` bytes in field order. The envelope
+/// is checked in that form, then lifted: inputs become `DatumValue::Text`,
+/// payload and logs stay in `payload_json` / `logs_json`.
+mod v1 {
+    use super::*;
+    use crate::{sha256_hex, Checksum, Constraints, Datum, HashAlgo, Intent, RunStatus};
+
+    #[derive(Serialize, Deserialize)]
+    struct Datum1 {
+        key: String,
+        value: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Intent1 {
+        intent_id: String,
+        created_unix_ms: u64,
+        operator: Option<String>,
+        statement: String,
+        inputs: Vec<Datum1>,
+        constraints: Constraints,
+        nonce: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct Request1 {
+        protocol: String,
+        intent: Intent1,
+        checksum: Checksum,
+    }
+
+    #[derive(Serialize)]
+    struct RequestToHash1<'a> {
+        protocol: &'a str,
+        intent: &'a Intent1,
+    }
+
+    #[derive(Deserialize)]
+    struct Response1 {
+        protocol: String,
+        intent_id: String,
+        status: RunStatus,
+        message: String,
+        payload_json: Option<String>,
+        logs_json: Option<String>,
+        checksum: Checksum,
+    }
+
+    #[derive(Serialize)]
+    struct ResponseToHash1<'a> {
+        protocol: &'a str,
+        intent_id: &'a str,
+        status: &'a RunStatus,
+        message: &'a str,
+        payload_json: &'a Option<String>,
+        logs_json: &'a Option<String>,
+    }
+
+    fn check_checksum(checksum: &Checksum, to_hash: &impl Serialize) -> Result<(), HandshakeError> {
+        if checksum.algo != HashAlgo::Sha256 {
+            return Err(HandshakeError::BadChecksumFormat);
+        }
+        let bytes = serde_json::to_vec(to_hash).map_err(|_| HandshakeError::BadJson)?;
+        if sha256_hex(&bytes) != checksum.hex {
+            return Err(HandshakeError::ChecksumMismatch);
+        }
+        Ok(())
+    }
+
+    pub(super) fn decode_request(bytes: &[u8]) -> Result<RequestEnvelope, HandshakeError> {
+        let wire: Request1 = serde_json::from_slice(bytes).map_err(|_| HandshakeError::BadJson)?;
+        check_checksum(
+            &wire.checksum,
+            &RequestToHash1 {
+                protocol: &wire.protocol,
+                intent: &wire.intent,
+            },
+        )?;
+
+        let intent = wire.intent;
+        let env = RequestEnvelope {
+            protocol: wire.protocol,
+            intent: Intent {
+                intent_id: intent.intent_id,
+                created_unix_ms: intent.created_unix_ms,
+                operator: intent.operator,
+                statement: intent.statement,
+                inputs: intent
+                    .inputs
+                    .into_iter()
+                    .map(|d| Datum::new(d.key, d.value))
+                    .collect(),
+                constraints: intent.constraints,
+                nonce: intent.nonce,
+            },
+            checksum: wire.checksum,
+            signature: None,
+        };
+        // The lifted envelope must still verify on its own
+        env.verify()?;
+        Ok(env)
+    }
+
+    pub(super) fn decode_response(bytes: &[u8]) -> Result<ResponseEnvelope, HandshakeError> {
+        let wire: Response1 = serde_json::from_slice(bytes).map_err(|_| HandshakeError::BadJson)?;
+        check_checksum(
+            &wire.checksum,
+            &ResponseToHash1 {
+                protocol: &wire.protocol,
+                intent_id: &wire.intent_id,
+                status: &wire.status,
+                message: &wire.message,
+                payload_json: &wire.payload_json,
+                logs_json: &wire.logs_json,
+            },
+        )?;
+
+        let env = ResponseEnvelope {
+            protocol: wire.protocol,
+            intent_id: wire.intent_id,
+            status: wire.status,
+            message: wire.message,
+            payload: None,
+            logs: None,
+            violations: None,
+            payload_json: wire.payload_json,
+            logs_json: wire.logs_json,
+            checksum: wire.checksum,
+        };
+        env.verify()?;
+        Ok(env)
+    }
+}
+
+/// `amethyst-pilgrim-handshake/2`: the in-memory types are the wire types.
+mod v2 {
+    use super::*;
+
+    pub(super) fn decode_request(bytes: &[u8]) -> Result<RequestEnvelope, HandshakeError> {
+        let env: RequestEnvelope =
+            serde_json::from_slice(bytes).map_err(|_| HandshakeError::BadJson)?;
+        env.verify()?;
+        Ok(env)
+    }
+
+    pub(super) fn decode_response(bytes: &[u8]) -> Result<ResponseEnvelope, HandshakeError> {
+        let env: ResponseEnvelope =
+            serde_json::from_slice(bytes).map_err(|_| HandshakeError::BadJson)?;
+        env.verify()?;
+        Ok(env)
+    }
+}
@@ -0,0 +1,97 @@
+use pilgrim_handshake::*;
+
+fn field_errors(json: &str) -> Vec<FieldError> {
+    match Constraints::from_json_string(json) {
+        Err(HandshakeError::InvalidConstraints(errors)) => errors,
+        other => panic!("expected field errors, got {other:?}"),
+    }
+}
+
+fn err(field: &str, problem: FieldProblem) -> FieldError {
+    FieldError {
+        field: field.to_string(),
+        problem,
+    }
+}
+
+#[test]
+fn constraints_parse_from_bridge_json() {
+    let json = r#"{"max_steps":500,"max_runtime_ms":2000,"require_logs":true,"privacy":"Sealed"}"#;
+    let c = Constraints::from_json_string(json).unwrap();
+    assert_eq!(
+        c,
+        Constraints {
+            max_steps: 500,
+            max_runtime_ms: 2000,
+            require_logs: true,
+            privacy: PrivacyTier::Sealed,
+        }
+    );
+
+    // Same shape as the envelope serialization
+    let roundtrip = serde_json::to_string(&Constraints::default()).unwrap();
+    assert_eq!(
+        Constraints::from_json_string(&roundtrip).unwrap(),
+        Constraints::default()
+    );
+}
+
+#[test]
+fn every_bad_field_is_reported() {
+    let json = r#"{"max_steps":0,"require_logs":"yes","privacy":"Secret","colour":"red"}"#;
+    assert_eq!(
+        field_errors(json),
+        vec![
+            err(
+                "max_steps",
+                FieldProblem::OutOfRange {
+                    min: 1,
+                    max: Constraints::MAX_STEPS_LIMIT.into(),
+                }
+            ),
+            err("max_runtime_ms", FieldProblem::Missing),
+            err("require_logs", FieldProblem::WrongType { expected: "bool" }),
+            err(
+                "privacy",
+                FieldProblem::UnknownVariant {
+                    got: "Secret".to_string(),
+                }
+            ),
+            err("colour", FieldProblem::UnknownField),
+        ]
+    );
+}
+
+#[test]
+fn limits_reject_negative_oversized_and_fractional_values() {
+    let json =
+        r#"{"max_steps":-1,"max_runtime_ms":86400001,"require_logs":false,"privacy":"Public"}"#;
+    let errors = field_errors(json);
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|e| matches!(e.problem, FieldProblem::OutOfRange { .. })));
+
+    let json = r#"{"max_steps":1.5,"max_runtime_ms":10,"require_logs":false,"privacy":"Public"}"#;
+    assert_eq!(
+        field_errors(json),
+        vec![err(
+            "max_steps",
+            FieldProblem::WrongType {
+                expected: "integer"
+            }
+        )]
+    );
+}
+
+#[test]
+fn non_object_json_is_bad_json() {
+    assert_eq!(
+        Constraints::from_json_string("not json"),
+        Err(HandshakeError::BadJson)
+    );
+    assert_eq!(
+        Constraints::from_json_string("[1,2]"),
+        Err(HandshakeError::BadJson)
+    );
+}
@@ -0,0 +1,167 @@
+use pilgrim_handshake::cbor;
+use pilgrim_handshake::*;
+use serde_json::{json, Value};
+
+fn hex_cbor(value: Value) -> String {
+    hex::encode(cbor::encode(&value).unwrap())
+}
+
+fn decode_hex(h: &str) -> Result<Value, HandshakeError> {
+    cbor::decode(&hex::decode(h).unwrap())
+}
+
+#[test]
+fn encodes_rfc8949_appendix_a_vectors() {
+    // Integers: shortest head
+    assert_eq!(hex_cbor(json!(0)), "00");
+    assert_eq!(hex_cbor(json!(23)), "17");
+    assert_eq!(hex_cbor(json!(24)), "1818");
+    assert_eq!(hex_cbor(json!(1000)), "1903e8");
+    assert_eq!(hex_cbor(json!(1000000)), "1a000f4240");
+    assert_eq!(hex_cbor(json!(1000000000000u64)), "1b000000e8d4a51000");
+    assert_eq!(hex_cbor(json!(u64::MAX)), "1bffffffffffffffff");
+    assert_eq!(hex_cbor(json!(-1)), "20");
+    assert_eq!(hex_cbor(json!(-1000)), "3903e7");
+
+    // Floats: shortest exact width
+    assert_eq!(hex_cbor(json!(0.0)), "f90000");
+    assert_eq!(hex_cbor(json!(-0.0)), "f98000");
+    assert_eq!(hex_cbor(json!(1.5)), "f93e00");
+    assert_eq!(hex_cbor(json!(65504.0)), "f97bff");
+    assert_eq!(hex_cbor(json!(5.960464477539063e-8)), "f90001");
+    assert_eq!(hex_cbor(json!(0.00006103515625)), "f90400");
+    assert_eq!(hex_cbor(json!(-4.0)), "f9c400");
+    assert_eq!(hex_cbor(json!(100000.0)), "fa47c35000");
+    assert_eq!(hex_cbor(json!(3.4028234663852886e38)), "fa7f7fffff");
+    assert_eq!(hex_cbor(json!(1.1)), "fb3ff199999999999a");
+    assert_eq!(hex_cbor(json!(-4.1)), "fbc010666666666666");
+
+    // Text, arrays, maps (keys sorted by encoded bytes)
+    assert_eq!(hex_cbor(json!("\u{00fc}")), "62c3bc");
+    assert_eq!(hex_cbor(json!([1, [2, 3]])), "8201820203");
+    assert_eq!(
+        hex_cbor(json!({"b": 1, "aa": 3, "a": 2})),
+        "a361610261620162616103"
+    );
+    assert_eq!(hex_cbor(json!([null, true, false])), "83f6f5f4");
+}
+
+#[test]
+fn decoding_accepts_only_the_canonical_form() {
+    assert_eq!(decode_hex("1903e8"), Ok(json!(1000)));
+    assert_eq!(decode_hex("a2616102616201"), Ok(json!({"a": 2, "b": 1})));
+
+    for bad in [
+        "1817",                 // 23 in a 1-byte argument
+        "190017",               // 23 in a 2-byte argument
+        "fa3fc00000",           // 1.5 as f32 (f16 is exact)
+        "f97e00",               // NaN
+        "f97c00",               // +∞
+        "a2616201616102",       // keys out of order
+        "a2616101616102",       // duplicate key
+        "9f01ff",               // indefinite-length array
+        "a1656279746573623030", // {"bytes": "00"} as a map, not a byte string
+        "c11a514b67b0",         // tag 1
+        "a1016161",             // non-text key
+        "0000",                 // trailing bytes
+        "62c3",                 // truncated text
+        "7bffffffffffffffff",   // length beyond input
+    ] {
+        assert_eq!(decode_hex(bad), Err(HandshakeError::BadCbor), "{bad}");
+    }
+}
+
+#[test]
+fn bytes_datums_are_cbor_byte_strings() {
+    assert_eq!(hex_cbor(json!({"bytes": "00ff10"})), "4300ff10");
+    assert_eq!(hex_cbor(json!({"bytes": ""})), "40");
+    assert_eq!(decode_hex("4300ff10"), Ok(json!({"bytes": "00ff10"})));
+
+    // Anything that is not a lowercase-hex datum stays a map
+    assert_eq!(
+        hex_cbor(json!({"bytes": "00FF"})),
+        "a16562797465736430304646"
+    );
+    assert_eq!(hex_cbor(json!({"bytes": "0"}))[..2], *"a1");
+    assert_eq!(hex_cbor(json!({"bytes": "00", "n": 1}))[..2], *"a2");
+
+    let datum = Datum::new("blob", DatumValue::Bytes(vec![0x00, 0xff, 0x10]));
+    let bytes = cbor::to_canonical_vec(&datum).unwrap();
+    assert!(hex::encode(&bytes).contains("4300ff10"));
+    let back: Datum = serde_json::from_value(cbor::decode(&bytes).unwrap()).unwrap();
+    assert_eq!(back, datum);
+}
+
+#[test]
+fn json_and_cbor_convert_losslessly() {
+    let json = r#"{"sensor":"probe-7","samples":[0.5,-12.25,1e-7,3.141592653589793,42,-9007199254740993],"ok":true,"note":null}"#;
+    let bytes = cbor::json_to_cbor(json).unwrap();
+    let back = cbor::cbor_to_json(&bytes).unwrap();
+
+    let a: Value = serde_json::from_str(json).unwrap();
+    let b: Value = serde_json::from_str(&back).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(cbor::json_to_cbor(&back).unwrap(), bytes);
+}
+
+fn intent(nonce: u64) -> Intent {
+    Intent {
+        intent_id: "intent-cbor".to_string(),
+        created_unix_ms: 1700000000000,
+        operator: Some("sensor-pipeline".to_string()),
+        statement: "Large numeric input set.".to_string(),
+        inputs: (0..64)
+            .map(|i| Datum {
+                key: format!("s{i}"),
+                value: DatumValue::Integer(i * 7),
+            })
+            .collect(),
+        constraints: Constraints::default(),
+        nonce,
+    }
+}
+
+#[test]
+fn cbor_checksum_covers_canonical_cbor() {
+    let env = RequestEnvelope::new_with_algo(intent(1), HashAlgo::Sha256Cbor).unwrap();
+    env.verify().unwrap();
+    assert_eq!(
+        env.seal_bytes().unwrap(),
+        cbor::encode(&json!({"protocol": env.protocol, "intent": env.intent})).unwrap()
+    );
+
+    // Same intent, different canonical bytes, different checksum
+    let jcs = RequestEnvelope::new(intent(1)).unwrap();
+    assert_ne!(env.checksum.hex, jcs.checksum.hex);
+
+    // Full u64 nonces have a CBOR form even though they have no JCS one
+    RequestEnvelope::new_with_algo(intent(u64::MAX), HashAlgo::Sha256Cbor)
+        .unwrap()
+        .verify()
+        .unwrap();
+}
+
+#[test]
+fn envelopes_roundtrip_through_cbor_and_json() {
+    let env = RequestEnvelope::new_with_algo(intent(2), HashAlgo::Sha256Cbor).unwrap();
+    let bytes = env.to_cbor().unwrap();
+    assert!(bytes.len() < serde_json::to_vec(&env).unwrap().len());
+
+    let back = RequestEnvelope::from_cbor(&bytes).unwrap();
+    assert_eq!(back, env);
+    back.verify().unwrap();
+
+    // Debugging path: CBOR → JSON → CBOR is byte-identical
+    let json = cbor::cbor_to_json(&bytes).unwrap();
+    assert_eq!(cbor::json_to_cbor(&json).unwrap(), bytes);
+
+    let resp = ResponseEnvelope::new_with_algo(
+        "intent-cbor".to_string(),
+        RunStatus::Accepted,
+        "Accepted.".to_string(),
+        HashAlgo::Sha256Cbor,
+    )
+    .unwrap();
+    let back = ResponseEnvelope::from_cbor(&resp.to_cbor().unwrap()).unwrap();
+    back.verify().unwrap();
+}
@@ -0,0 +1,185 @@
+use pilgrim_handshake::chunked::*;
+use pilgrim_handshake::merkle::{self, MerkleTree};
+use pilgrim_handshake::*;
+use sha2::{Digest, Sha256};
+
+fn intent() -> Intent {
+    Intent {
+        intent_id: "intent-dataset".to_string(),
+        created_unix_ms: 1700000000000,
+        operator: Some("lab-station-3".to_string()),
+        statement: "Fit the survey dataset.".to_string(),
+        inputs: vec![Datum::new("format", "csv")],
+        constraints: Constraints::default(),
+        nonce: 4,
+    }
+}
+
+fn payload(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+/// Upload `data` in uneven writes; return the sender and its manifest.
+fn upload(data: &[u8], chunk_size: u32, algo: HashAlgo) -> (ChunkedUpload, ManifestEnvelope) {
+    let mut up = ChunkedUpload::new(chunk_size, algo);
+    for part in data.chunks(777) {
+        up.update(part);
+    }
+    let manifest = up.finish(intent()).unwrap();
+    (up, manifest)
+}
+
+fn chunk(up: &ChunkedUpload, m: &ManifestEnvelope, data: &[u8], i: u32) -> ChunkEnvelope {
+    let start = m.chunk_offset(i) as usize;
+    let end = start + m.chunk_len(i) as usize;
+    up.chunk(i, &data[start..end]).unwrap()
+}
+
+/// RFC 9162 MTH, straight from the definition.
+fn reference_root(leaves: &[merkle::Hash]) -> merkle::Hash {
+    match leaves.len() {
+        0 => Sha256::digest([]).into(),
+        1 => leaves[0],
+        n => {
+            // Largest power of two below n
+            let k = n.next_power_of_two() / 2;
+            let mut h = Sha256::new();
+            h.update([0x01]);
+            h.update(reference_root(&leaves[..k]));
+            h.update(reference_root(&leaves[k..]));
+            h.finalize().into()
+        }
+    }
+}
+
+#[test]
+fn merkle_tree_matches_rfc9162_and_proves_every_leaf() {
+    for n in 0..=17u8 {
+        let leaves: Vec<_> = (0..n).map(|i| merkle::leaf_hash(&[i])).collect();
+        let tree = MerkleTree::from_leaves(leaves.clone());
+        assert_eq!(tree.root(), reference_root(&leaves), "n={n}");
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(i).unwrap();
+            let (i, size) = (i as u64, n as u64);
+            assert!(merkle::verify_proof(i, size, leaf, &proof, &tree.root()));
+            let forged = merkle::leaf_hash(b"forged");
+            assert!(!merkle::verify_proof(
+                i,
+                size,
+                &forged,
+                &proof,
+                &tree.root()
+            ));
+            if n > 1 {
+                let other = (i + 1) % size;
+                assert!(!merkle::verify_proof(
+                    other,
+                    size,
+                    leaf,
+                    &proof,
+                    &tree.root()
+                ));
+            }
+        }
+        assert_eq!(tree.proof(n as usize), None);
+    }
+}
+
+#[test]
+fn chunks_reassemble_in_any_order() {
+    let data = payload(10_500);
+    for algo in [HashAlgo::Sha256, HashAlgo::Sha256Cbor] {
+        let (up, manifest) = upload(&data, 1000, algo);
+        manifest.verify().unwrap();
+        assert_eq!(manifest.chunk_count, 11);
+        assert_eq!(manifest.payload_len, 10_500);
+
+        let mut rx = ChunkReceiver::new(manifest.clone()).unwrap();
+        let mut out = vec![0u8; data.len()];
+        for i in (0..manifest.chunk_count).rev() {
+            let env = chunk(&up, &manifest, &data, i);
+            // Chunks travel as ordinary JSON envelopes
+            let env: ChunkEnvelope =
+                serde_json::from_str(&serde_json::to_string(&env).unwrap()).unwrap();
+            let got = rx.accept(&env).unwrap();
+            let start = got.offset as usize;
+            out[start..start + got.data.len()].copy_from_slice(&got.data);
+        }
+        assert!(rx.is_complete());
+        assert_eq!(out, data);
+    }
+}
+
+#[test]
+fn transfers_resume_from_stored_indices() {
+    let data = payload(4096);
+    let (up, manifest) = upload(&data, 512, HashAlgo::Sha256);
+
+    let mut rx = ChunkReceiver::new(manifest.clone()).unwrap();
+    for i in [0, 2, 3, 7] {
+        rx.accept(&chunk(&up, &manifest, &data, i)).unwrap();
+    }
+    let stored: Vec<u32> = rx.received().collect();
+    drop(rx);
+
+    let mut rx = ChunkReceiver::resume(manifest.clone(), stored).unwrap();
+    assert_eq!(rx.missing().collect::<Vec<_>>(), vec![1, 4, 5, 6]);
+    for i in rx.missing().collect::<Vec<_>>() {
+        rx.accept(&chunk(&up, &manifest, &data, i)).unwrap();
+    }
+    assert!(rx.is_complete());
+
+    // Re-sent chunks verify again
+    rx.accept(&chunk(&up, &manifest, &data, 3)).unwrap();
+
+    assert_eq!(
+        ChunkReceiver::resume(manifest, [8]).err(),
+        Some(HandshakeError::BadChunk { index: 8 })
+    );
+}
+
+#[test]
+fn foreign_or_altered_chunks_are_refused() {
+    let data = payload(3000);
+    let (up, manifest) = upload(&data, 1000, HashAlgo::Sha256);
+    let mut rx = ChunkReceiver::new(manifest.clone()).unwrap();
+
+    // Same intent, different bytes: self-consistent, but not in this tree
+    let mut other = data.clone();
+    other[1500] ^= 1;
+    let (other_up, other_manifest) = upload(&other, 1000, HashAlgo::Sha256);
+    let foreign = chunk(&other_up, &other_manifest, &other, 1);
+    foreign.verify().unwrap();
+    assert_eq!(
+        rx.accept(&foreign),
+        Err(HandshakeError::BadChunk { index: 1 })
+    );
+
+    // Edited in transit: the chunk's own checksum catches it
+    let mut edited = chunk(&up, &manifest, &data, 0);
+    edited.data_hex.replace_range(0..2, "ff");
+    assert_eq!(rx.accept(&edited), Err(HandshakeError::ChecksumMismatch));
+
+    // Sender refuses bytes that were not hashed at that index
+    assert_eq!(
+        up.chunk(2, &data[..1000]).err(),
+        Some(HandshakeError::BadChunk { index: 2 })
+    );
+
+    let mut tampered = manifest;
+    tampered.payload_len += 1;
+    assert_eq!(
+        ChunkReceiver::new(tampered).err(),
+        Some(HandshakeError::ChecksumMismatch)
+    );
+    assert_eq!(rx.missing().count(), 3);
+}
+
+#[test]
+fn empty_payload_is_complete_on_arrival() {
+    let (_, manifest) = upload(&[], 1000, HashAlgo::Sha256);
+    assert_eq!(manifest.chunk_count, 0);
+    let rx = ChunkReceiver::new(manifest).unwrap();
+    assert!(rx.is_complete());
+}
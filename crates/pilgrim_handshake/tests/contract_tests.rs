@@ -10,18 +10,18 @@ fn request_envelope_roundtrip_verifies() {
         inputs: vec![
             Datum {
                 key: "alpha".to_string(),
-                value: "1".to_string(),
+                value: "1".into(),
             },
             Datum {
                 key: "beta".to_string(),
-                value: "2".to_string(),
+                value: "2".into(),
             },
         ],
         constraints: Constraints::default(),
         nonce: 7,
     };
 
-    let env = RequestEnvelope::new(intent);
+    let env = RequestEnvelope::new(intent).unwrap();
     env.verify().unwrap();
 }
 
@@ -31,7 +31,8 @@ fn response_envelope_roundtrip_verifies() {
         "intent-0001".to_string(),
         RunStatus::Accepted,
         "Accepted by deterministic core.".to_string(),
-    );
+    )
+    .unwrap();
     resp.verify().unwrap();
 }
 
@@ -47,10 +48,207 @@ fn checksum_detects_tamper() {
         nonce: 1,
     };
 
-    let mut env = RequestEnvelope::new(intent);
+    let mut env = RequestEnvelope::new(intent).unwrap();
     env.verify().unwrap();
 
     // Tamper after creation
     env.intent.statement = "Tampered".to_string();
     assert!(env.verify().is_err());
 }
+
+fn signed_intent() -> Intent {
+    Intent {
+        intent_id: "intent-0003".to_string(),
+        created_unix_ms: 1700000000000,
+        operator: Some("lab-station-7".to_string()),
+        statement: "Signed run".to_string(),
+        inputs: vec![],
+        constraints: Constraints::default(),
+        nonce: 3,
+    }
+}
+
+fn keypair(subject_id: &str, seed: u8) -> pilgrim_identity::Keypair {
+    pilgrim_identity::Keypair::from_secret_bytes(subject_id, &[seed; 32]).unwrap()
+}
+
+fn station() -> pilgrim_identity::Keypair {
+    keypair("lab-station-7", 1)
+}
+
+#[test]
+fn signed_envelope_verifies_against_trusted_key() {
+    let signer = station();
+    let trusted = vec![signer.identity().clone()];
+
+    let env = RequestEnvelope::new_signed(signed_intent(), &signer).unwrap();
+    let who = env.verify_signed(&trusted).unwrap();
+    assert_eq!(who.subject_id, "lab-station-7");
+
+    // Signature travels with the JSON form
+    let json = serde_json::to_string(&env).unwrap();
+    let back: RequestEnvelope = serde_json::from_str(&json).unwrap();
+    back.verify_signed(&trusted).unwrap();
+}
+
+#[test]
+fn recomputed_checksum_does_not_hide_tamper_from_signature() {
+    let signer = station();
+    let trusted = vec![signer.identity().clone()];
+
+    let env = RequestEnvelope::new_signed(signed_intent(), &signer).unwrap();
+
+    // Man-in-the-middle edits the intent and recomputes the checksum
+    let mut tampered = RequestEnvelope::new(Intent {
+        statement: "Tampered".to_string(),
+        ..env.intent.clone()
+    })
+    .unwrap();
+    tampered.signature = env.signature.clone();
+
+    tampered.verify().unwrap();
+    assert_eq!(
+        tampered.verify_signed(&trusted),
+        Err(HandshakeError::BadSignature)
+    );
+}
+
+#[test]
+fn unsigned_or_untrusted_envelopes_are_rejected() {
+    let signer = station();
+    let stranger = keypair("stranger", 2);
+    let trusted = vec![signer.identity().clone()];
+
+    let unsigned = RequestEnvelope::new(signed_intent()).unwrap();
+    assert_eq!(
+        unsigned.verify_signed(&trusted),
+        Err(HandshakeError::Unsigned)
+    );
+
+    let foreign = RequestEnvelope::new_signed(signed_intent(), &stranger).unwrap();
+    assert!(matches!(
+        foreign.verify_signed(&trusted),
+        Err(HandshakeError::UntrustedSigner { .. })
+    ));
+}
+
+#[test]
+fn signer_outside_trusted_list_is_rejected() {
+    // Claims the station's subject id, but holds a different key
+    let impostor = keypair("lab-station-7", 9);
+    let env = RequestEnvelope::new_signed(signed_intent(), &impostor).unwrap();
+    let untrusted = Err(HandshakeError::UntrustedSigner {
+        fingerprint: impostor.identity().pubkey_fingerprint.clone(),
+    });
+
+    let trusted = vec![
+        station().identity().clone(),
+        keypair("lab-station-8", 3).identity().clone(),
+    ];
+    assert_eq!(env.verify_signed(&trusted), untrusted);
+    assert_eq!(env.verify_signed(&[]), untrusted);
+
+    // Trust follows the key, not the claimed subject id
+    let trusted = vec![impostor.identity().clone()];
+    assert!(env.verify_signed(&trusted).is_ok());
+}
+
+fn run_payload() -> RunPayload {
+    RunPayload::new(
+        RunResult {
+            cartridge_id: "cognitive-drift".to_string(),
+            outputs: vec![Datum {
+                key: "verdict".to_string(),
+                value: "stable".into(),
+            }],
+        },
+        ReceiptPayload {
+            run_id: "run-0001".to_string(),
+            intent_statement: "Prove determinism of envelope checksum.".to_string(),
+            final_trace_hash: "ab".repeat(32),
+            steps: 3,
+            merkle_root: "ef".repeat(32),
+            salted: true,
+        },
+    )
+}
+
+#[test]
+fn typed_payload_is_covered_by_checksum() {
+    let resp = ResponseEnvelope::new(
+        "intent-0001".to_string(),
+        RunStatus::Completed,
+        "Run completed.".to_string(),
+    )
+    .unwrap()
+    .with_payload(run_payload())
+    .unwrap()
+    .with_logs(vec![StepLog {
+        index: 0,
+        name: "tick-0".to_string(),
+        checksum_hex: "cd".repeat(32),
+        len: 12,
+    }])
+    .unwrap();
+    resp.verify().unwrap();
+
+    // Decodes structurally in one pass
+    let json = serde_json::to_string(&resp).unwrap();
+    let back: ResponseEnvelope = serde_json::from_str(&json).unwrap();
+    back.verify().unwrap();
+    let payload = back.payload.as_ref().unwrap();
+    assert_eq!(payload.step_count(), 3);
+    assert_eq!(payload.trace_hash(), "ab".repeat(32));
+
+    let mut tampered = back.clone();
+    tampered.payload.as_mut().unwrap().receipt.steps = 4;
+    assert_eq!(tampered.verify(), Err(HandshakeError::ChecksumMismatch));
+}
+
+#[test]
+fn unknown_payload_schema_is_rejected() {
+    let mut payload = run_payload();
+    payload.schema_version = PAYLOAD_SCHEMA_VERSION + 1;
+    let resp = ResponseEnvelope::new(
+        "intent-0001".to_string(),
+        RunStatus::Completed,
+        "Run completed.".to_string(),
+    )
+    .unwrap()
+    .with_payload(payload)
+    .unwrap();
+
+    assert_eq!(
+        resp.verify(),
+        Err(HandshakeError::UnsupportedPayloadVersion {
+            got: PAYLOAD_SCHEMA_VERSION + 1,
+            supported: PAYLOAD_SCHEMA_VERSION,
+        })
+    );
+}
+
+/// Sealed by a peer still on payload schema 1 (no `merkle_root`).
+const SCHEMA_1_RESPONSE: &str = include_str!("vectors/schema1_response.json");
+
+#[test]
+fn schema_1_payloads_are_still_read() {
+    let resp = version::decode_response(SCHEMA_1_RESPONSE.as_bytes()).unwrap();
+    resp.verify().unwrap();
+    let payload = resp.payload.as_ref().unwrap();
+    assert_eq!(payload.schema_version, MIN_PAYLOAD_SCHEMA_VERSION);
+    assert_eq!(payload.merkle_root(), "");
+
+    // Re-encoded, it is still the peer's envelope
+    let bytes = serde_json::to_vec(&resp).unwrap();
+    assert_eq!(version::decode_response(&bytes), Ok(resp.clone()));
+
+    let mut too_old = resp.payload.unwrap();
+    too_old.schema_version = MIN_PAYLOAD_SCHEMA_VERSION - 1;
+    assert_eq!(
+        too_old.check_schema(),
+        Err(HandshakeError::UnsupportedPayloadVersion {
+            got: 0,
+            supported: PAYLOAD_SCHEMA_VERSION,
+        })
+    );
+}
@@ -0,0 +1,159 @@
+use amethyst_invariants::Enforced;
+use pilgrim_handshake::*;
+
+fn schema() -> InputSchema {
+    InputSchema::new(vec![
+        FieldSpec::required("sensor", DatumKind::Text).with_range(1, 16),
+        FieldSpec::required("samples", DatumKind::Int).with_range(1, 1000),
+        FieldSpec::optional("gain", DatumKind::Decimal).with_range(0, 2),
+        FieldSpec::optional("tag", DatumKind::Text).with_max_count(3),
+    ])
+    .with_max_inputs(6)
+}
+
+fn request(inputs: Vec<Datum>) -> RequestEnvelope {
+    RequestEnvelope::new(Intent {
+        intent_id: "intent-schema".to_string(),
+        created_unix_ms: 1700000000000,
+        operator: None,
+        statement: "Sample the probe.".to_string(),
+        inputs,
+        constraints: Constraints::default(),
+        nonce: 1,
+    })
+    .unwrap()
+}
+
+#[test]
+fn conforming_inputs_are_accepted() {
+    let env = request(vec![
+        Datum::new("sensor", "probe-7"),
+        Datum::new("samples", 250i64),
+        Datum::new(
+            "gain",
+            Decimal {
+                units: 200,
+                scale: 2,
+            },
+        ),
+        Datum::new("tag", "night"),
+        Datum::new("tag", "cold"),
+    ]);
+
+    let resp = env.accept(&schema()).unwrap();
+    resp.verify().unwrap();
+    assert_eq!(resp.status, RunStatus::Accepted);
+    assert_eq!(resp.violations, None);
+
+    // Optional keys may be absent
+    let env = request(vec![
+        Datum::new("sensor", "probe-7"),
+        Datum::new("samples", 1i64),
+    ]);
+    assert_eq!(env.accept(&schema()).unwrap().status, RunStatus::Accepted);
+}
+
+#[test]
+fn every_violation_is_listed_in_the_rejection() {
+    let env = request(vec![
+        Datum::new("samples", "250"),
+        Datum::new(
+            "gain",
+            Decimal {
+                units: 201,
+                scale: 2,
+            },
+        ),
+        Datum::new("tag", "a"),
+        Datum::new("tag", "b"),
+        Datum::new("tag", "c"),
+        Datum::new("tag", "d"),
+        Datum::new("sampels", 250i64),
+    ]);
+
+    let resp = env.accept(&schema()).unwrap();
+    resp.verify().unwrap();
+    assert_eq!(resp.status, RunStatus::Rejected);
+
+    let v = |key: &str, problem| InputViolation {
+        key: key.to_string(),
+        problem,
+    };
+    let violations = resp.violations.clone().unwrap();
+    assert_eq!(
+        violations,
+        vec![
+            v("", InputProblem::TooManyInputs { max: 6, got: 7 }),
+            v("sensor", InputProblem::Missing),
+            v(
+                "samples",
+                InputProblem::WrongType {
+                    expected: DatumKind::Int,
+                    got: DatumKind::Text,
+                }
+            ),
+            v("gain", InputProblem::OutOfRange { min: 0, max: 2 }),
+            v("tag", InputProblem::TooMany { max: 3, got: 4 }),
+            v("sampels", InputProblem::Unknown),
+        ]
+    );
+    let invariants: Vec<_> = violations.iter().map(|v| v.system_invariant()).collect();
+    // Everything but the unknown key is missing or malformed data
+    assert_eq!(invariants[..5], ["SAFE_002"; 5]);
+    assert_eq!(invariants[5], "TRUTH_002");
+    assert!(resp.message.contains("sensor: missing"));
+
+    // Violations are sealed: dropping them breaks the checksum
+    let mut tampered = resp.clone();
+    tampered.violations = None;
+    assert_eq!(tampered.verify(), Err(HandshakeError::ChecksumMismatch));
+}
+
+#[test]
+fn oversized_decimal_scales_are_out_of_range() {
+    // Built directly, so never through `DatumValue::validate`
+    for scale in [39, u8::MAX] {
+        let env = request(vec![
+            Datum::new("sensor", "probe-7"),
+            Datum::new("samples", 1i64),
+            Datum::new("gain", Decimal { units: 1, scale }),
+        ]);
+        let resp = env.accept(&schema()).unwrap();
+        assert_eq!(resp.status, RunStatus::Rejected);
+        assert_eq!(
+            resp.violations.unwrap(),
+            vec![InputViolation {
+                key: "gain".to_string(),
+                problem: InputProblem::OutOfRange { min: 0, max: 2 },
+            }]
+        );
+    }
+}
+
+#[test]
+fn integrity_failures_are_errors_not_rejections() {
+    let mut env = request(vec![Datum::new("sensor", "probe-7")]);
+    env.intent.nonce += 1;
+    assert_eq!(env.accept(&schema()), Err(HandshakeError::ChecksumMismatch));
+}
+
+#[test]
+fn responses_without_violations_keep_their_wire_form() {
+    let resp = ResponseEnvelope::new(
+        "intent-schema".to_string(),
+        RunStatus::Accepted,
+        "Accepted.".to_string(),
+    )
+    .unwrap();
+    let json = serde_json::to_value(&resp).unwrap();
+    assert!(json.get("violations").is_none());
+}
+
+#[test]
+fn schema_enforcement_maps_to_system_invariants() {
+    use amethyst_invariants::CoverageReport;
+
+    let report = CoverageReport::new(&[schema::ENFORCEMENT]);
+    assert_eq!(report.unknown().count(), 0);
+    assert!(report.enforced().any(|inv| inv.id == "SAFE_002"));
+}
@@ -0,0 +1,98 @@
+use pilgrim_handshake::jcs;
+use pilgrim_handshake::*;
+use serde_json::Value;
+
+const VECTORS: &str = include_str!("vectors/jcs_v1.json");
+
+fn vectors() -> Value {
+    serde_json::from_str(VECTORS).expect("vectors file is valid JSON")
+}
+
+#[test]
+fn number_vectors_match_ecmascript_form() {
+    for case in vectors()["numbers"].as_array().unwrap() {
+        let bits = u64::from_str_radix(case["ieee754_hex"].as_str().unwrap(), 16).unwrap();
+        let got = jcs::format_f64(f64::from_bits(bits)).unwrap();
+        assert_eq!(got, case["canonical"].as_str().unwrap(), "bits {bits:016x}");
+    }
+}
+
+#[test]
+fn value_vectors_canonicalize_byte_exact() {
+    for case in vectors()["values"].as_array().unwrap() {
+        let got = jcs::canonicalize(&case["input"]).unwrap();
+        assert_eq!(
+            String::from_utf8(got).unwrap(),
+            case["canonical"].as_str().unwrap()
+        );
+    }
+}
+
+#[test]
+fn envelope_vectors_seal_and_checksum() {
+    for case in vectors()["envelopes"].as_array().unwrap() {
+        let intent: Intent = serde_json::from_value(case["request"]["intent"].clone()).unwrap();
+        let env = RequestEnvelope::new(intent).unwrap();
+
+        assert_eq!(
+            String::from_utf8(env.seal_bytes().unwrap()).unwrap(),
+            case["seal"].as_str().unwrap()
+        );
+        assert_eq!(env.checksum.hex, case["checksum"].as_str().unwrap());
+    }
+}
+
+#[test]
+fn unsafe_integers_cannot_be_sealed() {
+    let intent = Intent {
+        intent_id: "intent-big".to_string(),
+        created_unix_ms: 1700000000000,
+        operator: None,
+        statement: "Nonce beyond 2^53".to_string(),
+        inputs: vec![],
+        constraints: Constraints::default(),
+        nonce: jcs::MAX_SAFE_INTEGER + 1,
+    };
+    assert_eq!(
+        RequestEnvelope::new(intent.clone()),
+        Err(HandshakeError::NumberOutOfRange)
+    );
+
+    // Random nonces folded into range always seal
+    let folded = Intent {
+        nonce: Intent::nonce_from_bits(u64::MAX),
+        ..intent.clone()
+    };
+    assert_eq!(folded.nonce, Intent::MAX_NONCE);
+    RequestEnvelope::new(folded).unwrap().verify().unwrap();
+
+    let resp = ResponseEnvelope::new(
+        "intent-big".to_string(),
+        RunStatus::Completed,
+        "Completed.".to_string(),
+    )
+    .unwrap();
+    let log = StepLog {
+        index: 0,
+        name: "tick-0".to_string(),
+        checksum_hex: "cd".repeat(32),
+        len: u64::MAX,
+    };
+    assert_eq!(
+        resp.with_logs(vec![log]),
+        Err(HandshakeError::NumberOutOfRange)
+    );
+
+    // Received from elsewhere, it fails closed whatever its checksum says
+    let env = RequestEnvelope {
+        protocol: PROTOCOL_VERSION.to_string(),
+        intent,
+        checksum: Checksum {
+            algo: HashAlgo::Sha256,
+            hex: String::new(),
+        },
+        signature: None,
+    };
+    assert_eq!(env.seal_bytes(), Err(HandshakeError::NumberOutOfRange));
+    assert_eq!(env.verify(), Err(HandshakeError::NumberOutOfRange));
+}
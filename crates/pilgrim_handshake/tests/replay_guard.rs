@@ -0,0 +1,167 @@
+use pilgrim_handshake::replay::*;
+use pilgrim_handshake::*;
+
+const NOW: u64 = 1_700_000_000_000;
+
+fn station() -> pilgrim_identity::Keypair {
+    pilgrim_identity::Keypair::from_secret_bytes("lab-station-7", &[1; 32]).unwrap()
+}
+
+fn trusted() -> Vec<pilgrim_identity::Identity> {
+    vec![station().identity().clone()]
+}
+
+fn envelope(intent_id: &str, nonce: u64, created_unix_ms: u64) -> RequestEnvelope {
+    let mut env = RequestEnvelope::new(Intent {
+        intent_id: intent_id.to_string(),
+        created_unix_ms,
+        operator: None,
+        statement: "Run once.".to_string(),
+        inputs: vec![],
+        constraints: Constraints::default(),
+        nonce,
+    })
+    .unwrap();
+    env.sign(&station()).unwrap();
+    env
+}
+
+fn guard(capacity: usize) -> ReplayGuard {
+    ReplayGuard::new(ReplayConfig {
+        max_age_ms: 60_000,
+        max_skew_ms: 1_000,
+        capacity,
+    })
+}
+
+#[test]
+fn captured_envelope_cannot_be_resubmitted() {
+    let mut guard = guard(16);
+    let env = envelope("intent-1", 1, NOW);
+
+    guard.admit(&env, &trusted(), NOW).unwrap();
+    assert_eq!(
+        guard.admit(&env, &trusted(), NOW + 10),
+        Err(HandshakeError::Replayed {
+            intent_id: "intent-1".to_string(),
+            nonce: 1,
+        })
+    );
+
+    // Same intent_id with a fresh nonce is a new request
+    guard
+        .admit(&envelope("intent-1", 2, NOW), &trusted(), NOW)
+        .unwrap();
+    assert_eq!(guard.len(), 2);
+}
+
+#[test]
+fn out_of_window_requests_are_rejected() {
+    let mut guard = guard(16);
+
+    let stale = envelope("intent-old", 1, NOW - 60_001);
+    assert_eq!(
+        guard.admit(&stale, &trusted(), NOW),
+        Err(HandshakeError::OutsideReplayWindow {
+            created_unix_ms: NOW - 60_001,
+            now_unix_ms: NOW,
+        })
+    );
+
+    let future = envelope("intent-future", 1, NOW + 1_001);
+    assert!(matches!(
+        guard.admit(&future, &trusted(), NOW),
+        Err(HandshakeError::OutsideReplayWindow { .. })
+    ));
+    assert!(guard.is_empty());
+}
+
+#[test]
+fn expired_entries_free_capacity_without_reopening_replays() {
+    let mut guard = guard(1);
+    let first = envelope("intent-1", 1, NOW);
+    guard.admit(&first, &trusted(), NOW).unwrap();
+
+    // Live entry: the store refuses rather than forgets
+    assert_eq!(
+        guard.admit(&envelope("intent-2", 1, NOW), &trusted(), NOW),
+        Err(HandshakeError::ReplayStoreFull)
+    );
+
+    // Once the first falls out of the window, it is forgotten...
+    let later = NOW + 60_001;
+    guard
+        .admit(&envelope("intent-2", 1, later), &trusted(), later)
+        .unwrap();
+    assert_eq!(guard.len(), 1);
+
+    // ...but resubmitting it is still refused, by the window
+    assert!(matches!(
+        guard.admit(&first, &trusted(), later),
+        Err(HandshakeError::OutsideReplayWindow { .. })
+    ));
+}
+
+#[test]
+fn tampered_envelopes_are_not_remembered() {
+    let mut guard = guard(16);
+    let mut env = envelope("intent-1", 1, NOW);
+    env.intent.nonce = 99;
+
+    assert_eq!(
+        guard.admit(&env, &trusted(), NOW),
+        Err(HandshakeError::ChecksumMismatch)
+    );
+    assert!(guard.is_empty());
+}
+
+#[test]
+fn only_envelopes_signed_by_trusted_keys_are_admitted() {
+    let mut guard = guard(16);
+
+    let mut unsigned = envelope("intent-1", 1, NOW);
+    unsigned.signature = None;
+    assert_eq!(
+        guard.admit(&unsigned, &trusted(), NOW),
+        Err(HandshakeError::Unsigned)
+    );
+
+    // A valid checksum is not enough: anyone can reseal a fresh nonce
+    let impostor = pilgrim_identity::Keypair::from_secret_bytes("lab-station-7", &[2; 32]).unwrap();
+    let mut forged = envelope("intent-1", 2, NOW);
+    forged.sign(&impostor).unwrap();
+    assert!(matches!(
+        guard.admit(&forged, &trusted(), NOW),
+        Err(HandshakeError::UntrustedSigner { .. })
+    ));
+    assert!(guard.is_empty());
+
+    let trusted = trusted();
+    let signer = guard
+        .admit(&envelope("intent-1", 3, NOW), &trusted, NOW)
+        .unwrap();
+    assert_eq!(signer, &trusted[0]);
+}
+
+#[test]
+fn clock_stepping_back_does_not_reopen_the_window() {
+    let mut guard = guard(16);
+    let first = envelope("intent-1", 1, NOW);
+    guard.admit(&first, &trusted(), NOW).unwrap();
+
+    // Far enough ahead that the first entry is forgotten...
+    let later = NOW + 60_001;
+    guard
+        .admit(&envelope("intent-2", 1, later), &trusted(), later)
+        .unwrap();
+    assert_eq!(guard.len(), 1);
+
+    // ...then the clock jumps back: the window stays where it was
+    assert_eq!(
+        guard.admit(&first, &trusted(), NOW),
+        Err(HandshakeError::OutsideReplayWindow {
+            created_unix_ms: NOW,
+            now_unix_ms: later,
+        })
+    );
+}
@@ -0,0 +1,99 @@
+use pilgrim_handshake::datum::MAX_DECIMAL_SCALE;
+use pilgrim_handshake::*;
+use serde_json::json;
+
+fn all_types() -> Vec<Datum> {
+    vec![
+        Datum::new("label", "probe-7"),
+        Datum::new("count", -42i64),
+        Datum::new(
+            "gain",
+            Decimal {
+                units: 150,
+                scale: 2,
+            },
+        ),
+        Datum::new("calibrated", true),
+        Datum::new("blob", DatumValue::Bytes(vec![0x00, 0xff, 0x10])),
+        Datum::new(
+            "window",
+            DatumValue::List(vec![
+                DatumValue::Integer(1),
+                DatumValue::List(vec!["nested".into()]),
+            ]),
+        ),
+    ]
+}
+
+#[test]
+fn each_type_has_one_wire_form() {
+    let wire = serde_json::to_value(all_types()).unwrap();
+    assert_eq!(
+        wire,
+        json!([
+            {"key": "label", "value": "probe-7"},
+            {"key": "count", "value": {"int": -42}},
+            {"key": "gain", "value": {"decimal": {"units": 150, "scale": 2}}},
+            {"key": "calibrated", "value": {"bool": true}},
+            {"key": "blob", "value": {"bytes": "00ff10"}},
+            {"key": "window", "value": {"list": [{"int": 1}, {"list": ["nested"]}]}},
+        ])
+    );
+
+    let back: Vec<Datum> = serde_json::from_value(wire).unwrap();
+    assert_eq!(back, all_types());
+}
+
+#[test]
+fn typed_inputs_seal_under_json_and_cbor() {
+    let intent = Intent {
+        intent_id: "intent-typed".to_string(),
+        created_unix_ms: 1700000000000,
+        operator: None,
+        statement: "Typed inputs.".to_string(),
+        inputs: all_types(),
+        constraints: Constraints::default(),
+        nonce: 1,
+    };
+
+    for algo in [HashAlgo::Sha256, HashAlgo::Sha256Cbor] {
+        let env = RequestEnvelope::new_with_algo(intent.clone(), algo).unwrap();
+        env.verify().unwrap();
+
+        let json: RequestEnvelope =
+            serde_json::from_str(&serde_json::to_string(&env).unwrap()).unwrap();
+        json.verify().unwrap();
+        let cbor = RequestEnvelope::from_cbor(&env.to_cbor().unwrap()).unwrap();
+        cbor.verify().unwrap();
+        assert_eq!(cbor.intent.inputs, all_types());
+    }
+}
+
+#[test]
+fn floats_and_ambiguous_spellings_are_rejected() {
+    for bad in [
+        json!(1.5),
+        json!(7),
+        json!(true),
+        json!({"int": 1.5}),
+        json!({"bytes": "00FF"}),
+        json!({"bytes": "0"}),
+        json!({"decimal": {"units": 1, "scale": MAX_DECIMAL_SCALE + 1}}),
+        json!({"float": 1.5}),
+        json!({"int": 1, "bool": true}),
+    ] {
+        assert!(
+            serde_json::from_value::<DatumValue>(bad.clone()).is_err(),
+            "{bad}"
+        );
+    }
+}
+
+#[test]
+fn decimals_display_with_their_scale() {
+    let d = |units, scale| Decimal { units, scale }.to_string();
+    assert_eq!(d(150, 2), "1.50");
+    assert_eq!(d(-5, 2), "-0.05");
+    assert_eq!(d(42, 0), "42");
+    assert_eq!(d(i64::MIN, 18), "-9.223372036854775808");
+}
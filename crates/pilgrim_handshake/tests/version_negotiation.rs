@@ -0,0 +1,267 @@
+use pilgrim_handshake::version::*;
+use pilgrim_handshake::*;
+
+fn intent() -> Intent {
+    Intent {
+        intent_id: "intent-0001".to_string(),
+        created_unix_ms: 1700000000000,
+        operator: None,
+        statement: "Negotiate before speaking.".to_string(),
+        inputs: vec![],
+        constraints: Constraints::default(),
+        nonce: 1,
+    }
+}
+
+fn range(min: u16, max: u16) -> VersionRange {
+    VersionRange::new(ProtocolVersion(min), ProtocolVersion(max))
+}
+
+#[test]
+fn protocol_version_string_matches_current() {
+    assert_eq!(ProtocolVersion::CURRENT.to_string(), PROTOCOL_VERSION);
+    assert_eq!(
+        ProtocolVersion::parse(PROTOCOL_VERSION),
+        Ok(ProtocolVersion::CURRENT)
+    );
+    assert!(SUPPORTED.contains(ProtocolVersion::CURRENT));
+}
+
+#[test]
+fn negotiation_upgrades_to_highest_common_version() {
+    let offer = ProtocolOffer {
+        supported: range(1, 5),
+    };
+    let accept = offer.negotiate(&range(2, 3)).unwrap();
+    assert_eq!(accept.selected, ProtocolVersion(3));
+    assert_eq!(accept.confirm(&range(1, 5)), Ok(ProtocolVersion(3)));
+
+    // Default offer against this build
+    let accept = ProtocolOffer::new().negotiate(&SUPPORTED).unwrap();
+    assert_eq!(accept.selected, ProtocolVersion::V2);
+
+    // A client still on /1 is answered in /1
+    let accept = ProtocolOffer {
+        supported: range(1, 1),
+    }
+    .negotiate(&SUPPORTED)
+    .unwrap();
+    assert_eq!(accept.selected, ProtocolVersion::V1);
+}
+
+#[test]
+fn negotiation_never_downgrades_below_local_min() {
+    let offer = ProtocolOffer {
+        supported: range(1, 1),
+    };
+    assert_eq!(
+        offer.negotiate(&range(2, 3)),
+        Err(HandshakeError::NoCommonVersion {
+            offered: range(1, 1),
+            supported: range(2, 3),
+        })
+    );
+
+    // A server cannot push a version the client never offered
+    let accept = ProtocolAccept {
+        selected: ProtocolVersion(1),
+    };
+    assert!(matches!(
+        accept.confirm(&range(2, 3)),
+        Err(HandshakeError::UnsupportedVersion { .. })
+    ));
+}
+
+#[test]
+fn negotiation_messages_roundtrip_as_json() {
+    let offer = ProtocolOffer::new();
+    let json = serde_json::to_string(&offer).unwrap();
+    assert!(json.contains(PROTOCOL_VERSION));
+    assert_eq!(serde_json::from_str::<ProtocolOffer>(&json).unwrap(), offer);
+
+    let bad = r#"{"selected":"other-protocol/1"}"#;
+    assert!(serde_json::from_str::<ProtocolAccept>(bad).is_err());
+}
+
+#[test]
+fn decode_dispatches_on_protocol_version() {
+    let env = RequestEnvelope::new(intent()).unwrap();
+    let bytes = serde_json::to_vec(&env).unwrap();
+    assert_eq!(decode_request(&bytes), Ok(env));
+
+    let resp = ResponseEnvelope::new(
+        "intent-0001".to_string(),
+        RunStatus::Accepted,
+        "ok".to_string(),
+    )
+    .unwrap();
+    let bytes = serde_json::to_vec(&resp).unwrap();
+    assert_eq!(decode_response(&bytes), Ok(resp));
+}
+
+#[test]
+fn unsupported_and_foreign_protocols_are_rejected() {
+    let mut env = RequestEnvelope::new(intent()).unwrap();
+    env.protocol = ProtocolVersion(3).to_string();
+    assert_eq!(
+        env.verify(),
+        Err(HandshakeError::UnsupportedVersion {
+            got: ProtocolVersion(3),
+            supported: SUPPORTED,
+        })
+    );
+    let bytes = serde_json::to_vec(&env).unwrap();
+    assert!(matches!(
+        decode_request(&bytes),
+        Err(HandshakeError::UnsupportedVersion { .. })
+    ));
+
+    env.protocol = "amethyst-pilgrim-handshake/v1".to_string();
+    assert!(matches!(
+        env.verify(),
+        Err(HandshakeError::ProtocolMismatch { .. })
+    ));
+}
+
+/// What `/1` hashed: `serde_json` bytes in struct field order.
+#[derive(serde::Serialize)]
+struct LegacyRequestToHash<'a> {
+    protocol: &'a str,
+    intent: &'a Intent,
+}
+
+#[test]
+fn v1_envelopes_keep_the_legacy_checksum() {
+    let current = RequestEnvelope::new(intent()).unwrap();
+
+    let mut env = current.clone();
+    env.protocol = ProtocolVersion::V1.to_string();
+    let legacy = serde_json::to_vec(&LegacyRequestToHash {
+        protocol: &env.protocol,
+        intent: &env.intent,
+    })
+    .unwrap();
+    assert_eq!(env.seal_bytes().unwrap(), legacy);
+    assert_ne!(current.seal_bytes().unwrap()[..], legacy[..]);
+
+    env.checksum.hex = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&legacy));
+    env.verify().unwrap();
+    let bytes = serde_json::to_vec(&env).unwrap();
+    assert_eq!(decode_request(&bytes), Ok(env.clone()));
+
+    // `/1` never had CBOR checksums
+    env.checksum.algo = HashAlgo::Sha256Cbor;
+    assert_eq!(env.verify(), Err(HandshakeError::BadChecksumFormat));
+}
+
+#[test]
+fn v1_responses_keep_their_untyped_fields() {
+    #[derive(serde::Serialize)]
+    struct LegacyResponseToHash<'a> {
+        protocol: &'a str,
+        intent_id: &'a str,
+        status: RunStatus,
+        message: &'a str,
+        payload_json: Option<&'a str>,
+        logs_json: Option<&'a str>,
+    }
+    let protocol = ProtocolVersion::V1.to_string();
+    let legacy = serde_json::to_vec(&LegacyResponseToHash {
+        protocol: &protocol,
+        intent_id: "intent-0001",
+        status: RunStatus::Completed,
+        message: "ok",
+        payload_json: Some(r#"{"verdict":"stable"}"#),
+        logs_json: None,
+    })
+    .unwrap();
+    let checksum = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(&legacy));
+
+    let wire = serde_json::json!({
+        "protocol": protocol,
+        "intent_id": "intent-0001",
+        "status": "Completed",
+        "message": "ok",
+        "payload_json": r#"{"verdict":"stable"}"#,
+        "logs_json": null,
+        "checksum": { "algo": "Sha256", "hex": checksum },
+    });
+    let resp = decode_response(&serde_json::to_vec(&wire).unwrap()).unwrap();
+    assert_eq!(
+        resp.payload_json.as_deref(),
+        Some(r#"{"verdict":"stable"}"#)
+    );
+    assert_eq!(resp.payload, None);
+
+    // Typed fields are `/2` only, untyped ones `/1` only
+    let mut typed = resp.clone();
+    typed.logs = Some(vec![]);
+    assert_eq!(typed.verify(), Err(HandshakeError::BadJson));
+
+    let mut untyped = ResponseEnvelope::new(
+        "intent-0001".to_string(),
+        RunStatus::Completed,
+        "ok".to_string(),
+    )
+    .unwrap();
+    untyped.payload_json = resp.payload_json.clone();
+    assert_eq!(untyped.verify(), Err(HandshakeError::BadJson));
+}
+
+/// Sealed by the `/1` release itself, before canonical checksums.
+const V1_REQUEST: &str = include_str!("vectors/v1_request.json");
+const V1_RESPONSE: &str = include_str!("vectors/v1_response.json");
+
+#[test]
+fn v1_fixtures_decode_into_current_types() {
+    let env = decode_request(V1_REQUEST.as_bytes()).unwrap();
+    assert_eq!(env.protocol, ProtocolVersion::V1.to_string());
+    assert_eq!(env.intent.intent_id, "intent-v1");
+    assert_eq!(
+        env.intent.inputs,
+        vec![
+            Datum::new("cartridge", "counter_v1"),
+            Datum::new("ticks", "3")
+        ]
+    );
+    assert_eq!(env.signature, None);
+    env.verify().unwrap();
+
+    let resp = decode_response(V1_RESPONSE.as_bytes()).unwrap();
+    assert_eq!(resp.status, RunStatus::Completed);
+    assert_eq!(
+        resp.payload_json.as_deref(),
+        Some(r#"{"verdict":"stable","steps":3}"#)
+    );
+    assert_eq!(
+        resp.logs_json.as_deref(),
+        Some(r#"["tick-0","tick-1","tick-2"]"#)
+    );
+    assert_eq!(resp.payload, None);
+    resp.verify().unwrap();
+}
+
+#[test]
+fn v1_fixtures_reject_tampering() {
+    let mut wire: serde_json::Value = serde_json::from_str(V1_REQUEST).unwrap();
+    wire["intent"]["inputs"][1]["value"] = "4".into();
+    assert_eq!(
+        decode_request(&serde_json::to_vec(&wire).unwrap()),
+        Err(HandshakeError::ChecksumMismatch)
+    );
+
+    // A typed datum was never valid `/1`
+    let mut wire: serde_json::Value = serde_json::from_str(V1_REQUEST).unwrap();
+    wire["intent"]["inputs"][1]["value"] = serde_json::json!({ "int": 3 });
+    assert_eq!(
+        decode_request(&serde_json::to_vec(&wire).unwrap()),
+        Err(HandshakeError::BadJson)
+    );
+
+    let mut wire: serde_json::Value = serde_json::from_str(V1_RESPONSE).unwrap();
+    wire["logs_json"] = "[]".into();
+    assert_eq!(
+        decode_response(&serde_json::to_vec(&wire).unwrap()),
+        Err(HandshakeError::ChecksumMismatch)
+    );
+}
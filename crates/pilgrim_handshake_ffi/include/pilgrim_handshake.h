@@ -0,0 +1,265 @@
+/* Amethyst Pilgrim Handshake — C ABI. Generated by cbindgen from src/lib.rs; do not edit. */
+
+#ifndef PILGRIM_HANDSHAKE_H
+#define PILGRIM_HANDSHAKE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result code for every `ph_*` function.
+ */
+typedef enum PhStatus {
+  PH_STATUS_OK = 0,
+  PH_STATUS_PROTOCOL_MISMATCH = 1,
+  PH_STATUS_UNSUPPORTED_VERSION = 2,
+  PH_STATUS_NO_COMMON_VERSION = 3,
+  PH_STATUS_CHECKSUM_MISMATCH = 4,
+  PH_STATUS_BAD_CHECKSUM_FORMAT = 5,
+  PH_STATUS_BAD_JSON = 6,
+  PH_STATUS_NUMBER_OUT_OF_RANGE = 7,
+  PH_STATUS_UNSIGNED = 8,
+  PH_STATUS_UNTRUSTED_SIGNER = 9,
+  PH_STATUS_BAD_SIGNATURE = 10,
+  PH_STATUS_INVALID_CONSTRAINTS = 11,
+  PH_STATUS_UNSUPPORTED_PAYLOAD_VERSION = 12,
+  PH_STATUS_REPLAYED = 13,
+  PH_STATUS_OUTSIDE_REPLAY_WINDOW = 14,
+  PH_STATUS_REPLAY_STORE_FULL = 15,
+  PH_STATUS_BAD_CBOR = 16,
+  PH_STATUS_BAD_MANIFEST = 17,
+  PH_STATUS_BAD_CHUNK = 18,
+  /**
+   * A required pointer argument was null.
+   */
+  PH_STATUS_NULL_ARGUMENT = 100,
+  /**
+   * A text argument was not valid UTF-8.
+   */
+  PH_STATUS_INVALID_UTF8 = 101,
+  /**
+   * Secret key bytes did not form a usable keypair.
+   */
+  PH_STATUS_INVALID_KEY = 102,
+  /**
+   * Internal panic caught at the boundary.
+   */
+  PH_STATUS_PANIC = 103,
+  /**
+   * An enum argument was not one of its declared values.
+   */
+  PH_STATUS_INVALID_ARGUMENT = 104,
+} PhStatus;
+
+/**
+ * Run status, mirrored from `pilgrim_handshake::RunStatus`.
+ */
+typedef enum PhRunStatus {
+  PH_RUN_STATUS_IDLE = 0,
+  PH_RUN_STATUS_ACCEPTED = 1,
+  PH_RUN_STATUS_REJECTED = 2,
+  PH_RUN_STATUS_RUNNING = 3,
+  PH_RUN_STATUS_COMPLETED = 4,
+  PH_RUN_STATUS_FAILED = 5,
+} PhRunStatus;
+
+/**
+ * Opaque request envelope handle.
+ */
+typedef struct PhRequest PhRequest;
+
+/**
+ * Opaque response envelope handle.
+ */
+typedef struct PhResponse PhResponse;
+
+/**
+ * Library-owned bytes handed to the caller. Release with `ph_buffer_free`.
+ */
+typedef struct PhBuffer {
+  uint8_t *data;
+  size_t len;
+} PhBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Build and seal a request from `Intent` JSON.
+ *
+ * # Safety
+ * `intent_json` must point to `len` readable bytes; `out` must be writable.
+ */
+enum PhStatus ph_request_new(const uint8_t *intent_json, size_t len, struct PhRequest **out);
+
+/**
+ * Decode and verify request envelope JSON of any supported protocol version.
+ *
+ * # Safety
+ * `json` must point to `len` readable bytes; `out` must be writable.
+ */
+enum PhStatus ph_request_from_json(const uint8_t *json, size_t len, struct PhRequest **out);
+
+/**
+ * Sign the request with a 32-byte Ed25519 secret key.
+ *
+ * # Safety
+ * `req` must be a live handle; `subject_id` must point to `subject_len`
+ * readable bytes; `secret_key` must point to 32 readable bytes.
+ */
+enum PhStatus ph_request_sign(struct PhRequest *req,
+                              const uint8_t *subject_id,
+                              size_t subject_len,
+                              const uint8_t *secret_key);
+
+/**
+ * Verify protocol + checksum.
+ *
+ * # Safety
+ * `req` must be a live handle.
+ */
+enum PhStatus ph_request_verify(const struct PhRequest *req);
+
+/**
+ * Canonical bytes covered by the checksum and signature
+ * (JCS, or CBOR for `Sha256Cbor` checksums).
+ *
+ * # Safety
+ * `req` must be a live handle; `out` must be writable.
+ */
+enum PhStatus ph_request_seal_bytes(const struct PhRequest *req, struct PhBuffer *out);
+
+/**
+ * Wire JSON of the whole envelope.
+ *
+ * # Safety
+ * `req` must be a live handle; `out` must be writable.
+ */
+enum PhStatus ph_request_to_json(const struct PhRequest *req, struct PhBuffer *out);
+
+/**
+ * Canonical CBOR of the whole envelope.
+ *
+ * # Safety
+ * `req` must be a live handle; `out` must be writable.
+ */
+enum PhStatus ph_request_to_cbor(const struct PhRequest *req, struct PhBuffer *out);
+
+/**
+ * Decode and verify a canonical CBOR request envelope.
+ *
+ * # Safety
+ * `cbor` must point to `len` readable bytes; `out` must be writable.
+ */
+enum PhStatus ph_request_from_cbor(const uint8_t *cbor, size_t len, struct PhRequest **out);
+
+/**
+ * Release a request handle. Null is a no-op.
+ *
+ * # Safety
+ * `req` must be null or a handle not yet freed.
+ */
+void ph_request_free(struct PhRequest *req);
+
+/**
+ * Build and seal a response.
+ *
+ * `status` is a `PhRunStatus` value. `payload_json` (`RunPayload` JSON)
+ * and `logs_json` (a JSON array of `StepLog`) are optional: pass null
+ * with a length of 0 to leave them out.
+ *
+ * # Safety
+ * Every pointer must be null or point to its given length of readable
+ * bytes (UTF-8 for `intent_id` / `message`); `out` must be writable.
+ */
+enum PhStatus ph_response_new(const uint8_t *intent_id,
+                              size_t intent_id_len,
+                              uint32_t status,
+                              const uint8_t *message,
+                              size_t message_len,
+                              const uint8_t *payload_json,
+                              size_t payload_len,
+                              const uint8_t *logs_json,
+                              size_t logs_len,
+                              struct PhResponse **out);
+
+/**
+ * Decode and verify response envelope JSON of any supported protocol version.
+ *
+ * # Safety
+ * `json` must point to `len` readable bytes; `out` must be writable.
+ */
+enum PhStatus ph_response_from_json(const uint8_t *json, size_t len, struct PhResponse **out);
+
+/**
+ * Verify protocol + checksum.
+ *
+ * # Safety
+ * `resp` must be a live handle.
+ */
+enum PhStatus ph_response_verify(const struct PhResponse *resp);
+
+/**
+ * Wire JSON of the whole envelope.
+ *
+ * # Safety
+ * `resp` must be a live handle; `out` must be writable.
+ */
+enum PhStatus ph_response_to_json(const struct PhResponse *resp, struct PhBuffer *out);
+
+/**
+ * Canonical CBOR of the whole envelope.
+ *
+ * # Safety
+ * `resp` must be a live handle; `out` must be writable.
+ */
+enum PhStatus ph_response_to_cbor(const struct PhResponse *resp, struct PhBuffer *out);
+
+/**
+ * Decode and verify a canonical CBOR response envelope.
+ *
+ * # Safety
+ * `cbor` must point to `len` readable bytes; `out` must be writable.
+ */
+enum PhStatus ph_response_from_cbor(const uint8_t *cbor, size_t len, struct PhResponse **out);
+
+/**
+ * Release a response handle. Null is a no-op.
+ *
+ * # Safety
+ * `resp` must be null or a handle not yet freed.
+ */
+void ph_response_free(struct PhResponse *resp);
+
+/**
+ * Validate constraints JSON (see `Constraints::from_json_string`).
+ *
+ * # Safety
+ * `json` must point to `len` readable bytes.
+ */
+enum PhStatus ph_constraints_validate(const uint8_t *json, size_t len);
+
+/**
+ * Release a buffer written by this library, then reset it to empty.
+ *
+ * # Safety
+ * `buf` must be null or point to a `PhBuffer` from this library that has
+ * not been freed.
+ */
+void ph_buffer_free(struct PhBuffer *buf);
+
+/**
+ * Static, NUL-terminated name of a status code (e.g. "BAD_JSON").
+ * Codes that are not a `PhStatus` are named "INVALID_ARGUMENT".
+ */
+const char *ph_status_name(uint32_t status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PILGRIM_HANDSHAKE_H */
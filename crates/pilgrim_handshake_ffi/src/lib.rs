@@ -0,0 +1,590 @@
+//! C ABI for Pilgrim Handshake envelopes.
+//!
+//! Header: `include/pilgrim_handshake.h`, generated by cbindgen. The
+//! `header` test fails when it is stale; regenerate with
+//! `PH_REGEN_HEADER=1 cargo test -p pilgrim_handshake_ffi --test header`.
+//!
+//! Ownership rules:
+//! - input buffers (`*const u8` + `len`) are borrowed for the call only
+//! - `PhRequest` / `PhResponse` handles are owned by the caller once
+//!   returned, and released with `ph_request_free` / `ph_response_free`
+//! - `PhBuffer`s written by this library are owned by the caller and
+//!   released with `ph_buffer_free`; never with `free()`
+//! - on error, out-parameters are left untouched
+//!
+//! Every function returns a `PhStatus`. Codes below 100 mirror
+//! `HandshakeError` one-to-one; codes from 100 are FFI-only.
+//! Panics never cross the boundary: they surface as `PH_STATUS_PANIC`.
+//! Enum arguments are taken as `uint32_t` and range-checked, so an
+//! unknown value is `PH_STATUS_INVALID_ARGUMENT`, never undefined behaviour.
+
+use pilgrim_handshake::{
+    version, Constraints, HandshakeError, Intent, RequestEnvelope, ResponseEnvelope, RunPayload,
+    RunStatus, StepLog,
+};
+use pilgrim_identity::Keypair;
+use std::ffi::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Result code for every `ph_*` function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhStatus {
+    Ok = 0,
+    ProtocolMismatch = 1,
+    UnsupportedVersion = 2,
+    NoCommonVersion = 3,
+    ChecksumMismatch = 4,
+    BadChecksumFormat = 5,
+    BadJson = 6,
+    NumberOutOfRange = 7,
+    Unsigned = 8,
+    UntrustedSigner = 9,
+    BadSignature = 10,
+    InvalidConstraints = 11,
+    UnsupportedPayloadVersion = 12,
+    Replayed = 13,
+    OutsideReplayWindow = 14,
+    ReplayStoreFull = 15,
+    BadCbor = 16,
+    BadManifest = 17,
+    BadChunk = 18,
+    /// A required pointer argument was null.
+    NullArgument = 100,
+    /// A text argument was not valid UTF-8.
+    InvalidUtf8 = 101,
+    /// Secret key bytes did not form a usable keypair.
+    InvalidKey = 102,
+    /// Internal panic caught at the boundary.
+    Panic = 103,
+    /// An enum argument was not one of its declared values.
+    InvalidArgument = 104,
+}
+
+impl TryFrom<u32> for PhStatus {
+    type Error = PhStatus;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        Ok(match code {
+            0 => PhStatus::Ok,
+            1 => PhStatus::ProtocolMismatch,
+            2 => PhStatus::UnsupportedVersion,
+            3 => PhStatus::NoCommonVersion,
+            4 => PhStatus::ChecksumMismatch,
+            5 => PhStatus::BadChecksumFormat,
+            6 => PhStatus::BadJson,
+            7 => PhStatus::NumberOutOfRange,
+            8 => PhStatus::Unsigned,
+            9 => PhStatus::UntrustedSigner,
+            10 => PhStatus::BadSignature,
+            11 => PhStatus::InvalidConstraints,
+            12 => PhStatus::UnsupportedPayloadVersion,
+            13 => PhStatus::Replayed,
+            14 => PhStatus::OutsideReplayWindow,
+            15 => PhStatus::ReplayStoreFull,
+            16 => PhStatus::BadCbor,
+            17 => PhStatus::BadManifest,
+            18 => PhStatus::BadChunk,
+            100 => PhStatus::NullArgument,
+            101 => PhStatus::InvalidUtf8,
+            102 => PhStatus::InvalidKey,
+            103 => PhStatus::Panic,
+            104 => PhStatus::InvalidArgument,
+            _ => return Err(PhStatus::InvalidArgument),
+        })
+    }
+}
+
+impl From<&HandshakeError> for PhStatus {
+    fn from(e: &HandshakeError) -> Self {
+        match e {
+            HandshakeError::ProtocolMismatch { .. } => PhStatus::ProtocolMismatch,
+            HandshakeError::UnsupportedVersion { .. } => PhStatus::UnsupportedVersion,
+            HandshakeError::NoCommonVersion { .. } => PhStatus::NoCommonVersion,
+            HandshakeError::ChecksumMismatch => PhStatus::ChecksumMismatch,
+            HandshakeError::BadChecksumFormat => PhStatus::BadChecksumFormat,
+            HandshakeError::BadJson => PhStatus::BadJson,
+            HandshakeError::BadCbor => PhStatus::BadCbor,
+            HandshakeError::NumberOutOfRange => PhStatus::NumberOutOfRange,
+            HandshakeError::Unsigned => PhStatus::Unsigned,
+            HandshakeError::UntrustedSigner { .. } => PhStatus::UntrustedSigner,
+            HandshakeError::BadSignature => PhStatus::BadSignature,
+            HandshakeError::InvalidConstraints(_) => PhStatus::InvalidConstraints,
+            HandshakeError::UnsupportedPayloadVersion { .. } => PhStatus::UnsupportedPayloadVersion,
+            HandshakeError::Replayed { .. } => PhStatus::Replayed,
+            HandshakeError::OutsideReplayWindow { .. } => PhStatus::OutsideReplayWindow,
+            HandshakeError::ReplayStoreFull => PhStatus::ReplayStoreFull,
+            HandshakeError::BadManifest => PhStatus::BadManifest,
+            HandshakeError::BadChunk { .. } => PhStatus::BadChunk,
+        }
+    }
+}
+
+impl From<HandshakeError> for PhStatus {
+    fn from(e: HandshakeError) -> Self {
+        PhStatus::from(&e)
+    }
+}
+
+/// Run status, mirrored from `pilgrim_handshake::RunStatus`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhRunStatus {
+    Idle = 0,
+    Accepted = 1,
+    Rejected = 2,
+    Running = 3,
+    Completed = 4,
+    Failed = 5,
+}
+
+impl TryFrom<u32> for PhRunStatus {
+    type Error = PhStatus;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        Ok(match code {
+            0 => PhRunStatus::Idle,
+            1 => PhRunStatus::Accepted,
+            2 => PhRunStatus::Rejected,
+            3 => PhRunStatus::Running,
+            4 => PhRunStatus::Completed,
+            5 => PhRunStatus::Failed,
+            _ => return Err(PhStatus::InvalidArgument),
+        })
+    }
+}
+
+impl From<PhRunStatus> for RunStatus {
+    fn from(s: PhRunStatus) -> Self {
+        match s {
+            PhRunStatus::Idle => RunStatus::Idle,
+            PhRunStatus::Accepted => RunStatus::Accepted,
+            PhRunStatus::Rejected => RunStatus::Rejected,
+            PhRunStatus::Running => RunStatus::Running,
+            PhRunStatus::Completed => RunStatus::Completed,
+            PhRunStatus::Failed => RunStatus::Failed,
+        }
+    }
+}
+
+/// Library-owned bytes handed to the caller. Release with `ph_buffer_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PhBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl PhBuffer {
+    pub const EMPTY: PhBuffer = PhBuffer {
+        data: std::ptr::null_mut(),
+        len: 0,
+    };
+
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let boxed = bytes.into_boxed_slice();
+        let len = boxed.len();
+        PhBuffer {
+            data: Box::into_raw(boxed) as *mut u8,
+            len,
+        }
+    }
+
+    /// View of the bytes (for Rust callers and tests).
+    ///
+    /// # Safety
+    /// `self` must come from this library and not have been freed.
+    pub unsafe fn as_slice(&self) -> &[u8] {
+        if self.data.is_null() {
+            return &[];
+        }
+        std::slice::from_raw_parts(self.data, self.len)
+    }
+}
+
+/// Opaque request envelope handle.
+pub struct PhRequest(RequestEnvelope);
+
+/// Opaque response envelope handle.
+pub struct PhResponse(ResponseEnvelope);
+
+fn guard(f: impl FnOnce() -> Result<(), PhStatus>) -> PhStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => PhStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => PhStatus::Panic,
+    }
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], PhStatus> {
+    if data.is_null() {
+        return if len == 0 {
+            Ok(&[])
+        } else {
+            Err(PhStatus::NullArgument)
+        };
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+unsafe fn text<'a>(data: *const u8, len: usize) -> Result<&'a str, PhStatus> {
+    std::str::from_utf8(bytes(data, len)?).map_err(|_| PhStatus::InvalidUtf8)
+}
+
+unsafe fn handle<'a, T>(ptr: *const T) -> Result<&'a T, PhStatus> {
+    ptr.as_ref().ok_or(PhStatus::NullArgument)
+}
+
+unsafe fn write_out<T>(out: *mut T, value: T) -> Result<(), PhStatus> {
+    if out.is_null() {
+        return Err(PhStatus::NullArgument);
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// Build and seal a request from `Intent` JSON.
+///
+/// # Safety
+/// `intent_json` must point to `len` readable bytes; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_new(
+    intent_json: *const u8,
+    len: usize,
+    out: *mut *mut PhRequest,
+) -> PhStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(PhStatus::NullArgument);
+        }
+        let intent: Intent =
+            serde_json::from_slice(bytes(intent_json, len)?).map_err(|_| PhStatus::BadJson)?;
+        let env = RequestEnvelope::new(intent)?;
+        write_out(out, Box::into_raw(Box::new(PhRequest(env))))
+    })
+}
+
+/// Decode and verify request envelope JSON of any supported protocol version.
+///
+/// # Safety
+/// `json` must point to `len` readable bytes; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_from_json(
+    json: *const u8,
+    len: usize,
+    out: *mut *mut PhRequest,
+) -> PhStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(PhStatus::NullArgument);
+        }
+        let env = version::decode_request(bytes(json, len)?)?;
+        write_out(out, Box::into_raw(Box::new(PhRequest(env))))
+    })
+}
+
+/// Sign the request with a 32-byte Ed25519 secret key.
+///
+/// # Safety
+/// `req` must be a live handle; `subject_id` must point to `subject_len`
+/// readable bytes; `secret_key` must point to 32 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_sign(
+    req: *mut PhRequest,
+    subject_id: *const u8,
+    subject_len: usize,
+    secret_key: *const u8,
+) -> PhStatus {
+    guard(|| {
+        let req = req.as_mut().ok_or(PhStatus::NullArgument)?;
+        let subject = text(subject_id, subject_len)?;
+        if secret_key.is_null() {
+            return Err(PhStatus::NullArgument);
+        }
+        let secret: [u8; 32] = secret_key.cast::<[u8; 32]>().read_unaligned();
+        let keypair =
+            Keypair::from_secret_bytes(subject, &secret).map_err(|_| PhStatus::InvalidKey)?;
+        req.0.sign(&keypair)?;
+        Ok(())
+    })
+}
+
+/// Verify protocol + checksum.
+///
+/// # Safety
+/// `req` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_verify(req: *const PhRequest) -> PhStatus {
+    guard(|| Ok(handle(req)?.0.verify()?))
+}
+
+/// Canonical bytes covered by the checksum and signature
+/// (JCS, or CBOR for `Sha256Cbor` checksums).
+///
+/// # Safety
+/// `req` must be a live handle; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_seal_bytes(
+    req: *const PhRequest,
+    out: *mut PhBuffer,
+) -> PhStatus {
+    guard(|| {
+        let sealed = handle(req)?.0.seal_bytes()?;
+        write_out(out, PhBuffer::from_vec(sealed))
+    })
+}
+
+/// Wire JSON of the whole envelope.
+///
+/// # Safety
+/// `req` must be a live handle; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_to_json(req: *const PhRequest, out: *mut PhBuffer) -> PhStatus {
+    guard(|| {
+        let json = serde_json::to_vec(&handle(req)?.0).map_err(|_| PhStatus::BadJson)?;
+        write_out(out, PhBuffer::from_vec(json))
+    })
+}
+
+/// Canonical CBOR of the whole envelope.
+///
+/// # Safety
+/// `req` must be a live handle; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_to_cbor(req: *const PhRequest, out: *mut PhBuffer) -> PhStatus {
+    guard(|| {
+        let cbor = handle(req)?.0.to_cbor()?;
+        write_out(out, PhBuffer::from_vec(cbor))
+    })
+}
+
+/// Decode and verify a canonical CBOR request envelope.
+///
+/// # Safety
+/// `cbor` must point to `len` readable bytes; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_from_cbor(
+    cbor: *const u8,
+    len: usize,
+    out: *mut *mut PhRequest,
+) -> PhStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(PhStatus::NullArgument);
+        }
+        let env = RequestEnvelope::from_cbor(bytes(cbor, len)?)?;
+        env.verify()?;
+        write_out(out, Box::into_raw(Box::new(PhRequest(env))))
+    })
+}
+
+/// Release a request handle. Null is a no-op.
+///
+/// # Safety
+/// `req` must be null or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn ph_request_free(req: *mut PhRequest) {
+    if !req.is_null() {
+        drop(Box::from_raw(req));
+    }
+}
+
+/// Build and seal a response.
+///
+/// `status` is a `PhRunStatus` value. `payload_json` (`RunPayload` JSON)
+/// and `logs_json` (a JSON array of `StepLog`) are optional: pass null
+/// with a length of 0 to leave them out.
+///
+/// # Safety
+/// Every pointer must be null or point to its given length of readable
+/// bytes (UTF-8 for `intent_id` / `message`); `out` must be writable.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn ph_response_new(
+    intent_id: *const u8,
+    intent_id_len: usize,
+    status: u32,
+    message: *const u8,
+    message_len: usize,
+    payload_json: *const u8,
+    payload_len: usize,
+    logs_json: *const u8,
+    logs_len: usize,
+    out: *mut *mut PhResponse,
+) -> PhStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(PhStatus::NullArgument);
+        }
+        let status = PhRunStatus::try_from(status)?;
+        let mut env = ResponseEnvelope::new(
+            text(intent_id, intent_id_len)?.to_string(),
+            status.into(),
+            text(message, message_len)?.to_string(),
+        )?;
+        if !payload_json.is_null() {
+            let payload: RunPayload = serde_json::from_slice(bytes(payload_json, payload_len)?)
+                .map_err(|_| PhStatus::BadJson)?;
+            env = env.with_payload(payload)?;
+        }
+        if !logs_json.is_null() {
+            let logs: Vec<StepLog> = serde_json::from_slice(bytes(logs_json, logs_len)?)
+                .map_err(|_| PhStatus::BadJson)?;
+            env = env.with_logs(logs)?;
+        }
+        write_out(out, Box::into_raw(Box::new(PhResponse(env))))
+    })
+}
+
+/// Decode and verify response envelope JSON of any supported protocol version.
+///
+/// # Safety
+/// `json` must point to `len` readable bytes; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_response_from_json(
+    json: *const u8,
+    len: usize,
+    out: *mut *mut PhResponse,
+) -> PhStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(PhStatus::NullArgument);
+        }
+        let env = version::decode_response(bytes(json, len)?)?;
+        write_out(out, Box::into_raw(Box::new(PhResponse(env))))
+    })
+}
+
+/// Verify protocol + checksum.
+///
+/// # Safety
+/// `resp` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ph_response_verify(resp: *const PhResponse) -> PhStatus {
+    guard(|| Ok(handle(resp)?.0.verify()?))
+}
+
+/// Wire JSON of the whole envelope.
+///
+/// # Safety
+/// `resp` must be a live handle; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_response_to_json(
+    resp: *const PhResponse,
+    out: *mut PhBuffer,
+) -> PhStatus {
+    guard(|| {
+        let json = serde_json::to_vec(&handle(resp)?.0).map_err(|_| PhStatus::BadJson)?;
+        write_out(out, PhBuffer::from_vec(json))
+    })
+}
+
+/// Canonical CBOR of the whole envelope.
+///
+/// # Safety
+/// `resp` must be a live handle; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_response_to_cbor(
+    resp: *const PhResponse,
+    out: *mut PhBuffer,
+) -> PhStatus {
+    guard(|| {
+        let cbor = handle(resp)?.0.to_cbor()?;
+        write_out(out, PhBuffer::from_vec(cbor))
+    })
+}
+
+/// Decode and verify a canonical CBOR response envelope.
+///
+/// # Safety
+/// `cbor` must point to `len` readable bytes; `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn ph_response_from_cbor(
+    cbor: *const u8,
+    len: usize,
+    out: *mut *mut PhResponse,
+) -> PhStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(PhStatus::NullArgument);
+        }
+        let env = ResponseEnvelope::from_cbor(bytes(cbor, len)?)?;
+        env.verify()?;
+        write_out(out, Box::into_raw(Box::new(PhResponse(env))))
+    })
+}
+
+/// Release a response handle. Null is a no-op.
+///
+/// # Safety
+/// `resp` must be null or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn ph_response_free(resp: *mut PhResponse) {
+    if !resp.is_null() {
+        drop(Box::from_raw(resp));
+    }
+}
+
+/// Validate constraints JSON (see `Constraints::from_json_string`).
+///
+/// # Safety
+/// `json` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ph_constraints_validate(json: *const u8, len: usize) -> PhStatus {
+    guard(|| {
+        Constraints::from_json_string(text(json, len)?)?;
+        Ok(())
+    })
+}
+
+/// Release a buffer written by this library, then reset it to empty.
+///
+/// # Safety
+/// `buf` must be null or point to a `PhBuffer` from this library that has
+/// not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ph_buffer_free(buf: *mut PhBuffer) {
+    let Some(buf) = buf.as_mut() else {
+        return;
+    };
+    if !buf.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            buf.data, buf.len,
+        )));
+    }
+    *buf = PhBuffer::EMPTY;
+}
+
+/// Static, NUL-terminated name of a status code (e.g. "BAD_JSON").
+/// Codes that are not a `PhStatus` are named "INVALID_ARGUMENT".
+#[no_mangle]
+pub extern "C" fn ph_status_name(status: u32) -> *const c_char {
+    let status = PhStatus::try_from(status).unwrap_or_else(|invalid| invalid);
+    let name: &'static [u8] = match status {
+        PhStatus::Ok => b"OK\0",
+        PhStatus::ProtocolMismatch => b"PROTOCOL_MISMATCH\0",
+        PhStatus::UnsupportedVersion => b"UNSUPPORTED_VERSION\0",
+        PhStatus::NoCommonVersion => b"NO_COMMON_VERSION\0",
+        PhStatus::ChecksumMismatch => b"CHECKSUM_MISMATCH\0",
+        PhStatus::BadChecksumFormat => b"BAD_CHECKSUM_FORMAT\0",
+        PhStatus::BadJson => b"BAD_JSON\0",
+        PhStatus::NumberOutOfRange => b"NUMBER_OUT_OF_RANGE\0",
+        PhStatus::Unsigned => b"UNSIGNED\0",
+        PhStatus::UntrustedSigner => b"UNTRUSTED_SIGNER\0",
+        PhStatus::BadSignature => b"BAD_SIGNATURE\0",
+        PhStatus::InvalidConstraints => b"INVALID_CONSTRAINTS\0",
+        PhStatus::UnsupportedPayloadVersion => b"UNSUPPORTED_PAYLOAD_VERSION\0",
+        PhStatus::Replayed => b"REPLAYED\0",
+        PhStatus::OutsideReplayWindow => b"OUTSIDE_REPLAY_WINDOW\0",
+        PhStatus::ReplayStoreFull => b"REPLAY_STORE_FULL\0",
+        PhStatus::BadCbor => b"BAD_CBOR\0",
+        PhStatus::BadManifest => b"BAD_MANIFEST\0",
+        PhStatus::BadChunk => b"BAD_CHUNK\0",
+        PhStatus::NullArgument => b"NULL_ARGUMENT\0",
+        PhStatus::InvalidUtf8 => b"INVALID_UTF8\0",
+        PhStatus::InvalidKey => b"INVALID_KEY\0",
+        PhStatus::Panic => b"PANIC\0",
+        PhStatus::InvalidArgument => b"INVALID_ARGUMENT\0",
+    };
+    name.as_ptr().cast()
+}
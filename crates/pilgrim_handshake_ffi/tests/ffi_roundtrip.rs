@@ -0,0 +1,287 @@
+use pilgrim_handshake::{
+    Datum, ReceiptPayload, RequestEnvelope, ResponseEnvelope, RunPayload, RunResult, StepLog,
+};
+use pilgrim_handshake_ffi::*;
+use std::ffi::CStr;
+use std::ptr;
+
+const INTENT: &str = r#"{
+    "intent_id": "intent-ffi-1",
+    "created_unix_ms": 1700000000000,
+    "operator": "lab-station-3",
+    "statement": "Seal from C.",
+    "inputs": [{"key": "alpha", "value": "1"}],
+    "constraints": {"max_steps": 100, "max_runtime_ms": 1000, "require_logs": true, "privacy": "Protected"},
+    "nonce": 9
+}"#;
+
+unsafe fn new_request() -> *mut PhRequest {
+    let mut req = ptr::null_mut();
+    assert_eq!(
+        ph_request_new(INTENT.as_ptr(), INTENT.len(), &mut req),
+        PhStatus::Ok
+    );
+    req
+}
+
+#[test]
+fn request_builds_serializes_and_decodes() {
+    unsafe {
+        let req = new_request();
+        assert_eq!(ph_request_verify(req), PhStatus::Ok);
+
+        let mut json = PhBuffer::EMPTY;
+        assert_eq!(ph_request_to_json(req, &mut json), PhStatus::Ok);
+        let env: RequestEnvelope = serde_json::from_slice(json.as_slice()).unwrap();
+        env.verify().unwrap();
+
+        let mut sealed = PhBuffer::EMPTY;
+        assert_eq!(ph_request_seal_bytes(req, &mut sealed), PhStatus::Ok);
+        assert_eq!(sealed.as_slice(), env.seal_bytes().unwrap());
+
+        let mut decoded = ptr::null_mut();
+        assert_eq!(
+            ph_request_from_json(json.data, json.len, &mut decoded),
+            PhStatus::Ok
+        );
+        assert_eq!(ph_request_verify(decoded), PhStatus::Ok);
+
+        ph_buffer_free(&mut json);
+        assert!(json.data.is_null());
+        ph_buffer_free(&mut sealed);
+        ph_request_free(decoded);
+        ph_request_free(req);
+    }
+}
+
+#[test]
+fn signed_request_verifies_in_rust() {
+    unsafe {
+        let req = new_request();
+        let subject = "lab-station-3";
+        let secret = [5u8; 32];
+        assert_eq!(
+            ph_request_sign(req, subject.as_ptr(), subject.len(), secret.as_ptr()),
+            PhStatus::Ok
+        );
+
+        let mut json = PhBuffer::EMPTY;
+        assert_eq!(ph_request_to_json(req, &mut json), PhStatus::Ok);
+        let env: RequestEnvelope = serde_json::from_slice(json.as_slice()).unwrap();
+        let trusted = pilgrim_identity::Keypair::from_secret_bytes(subject, &secret)
+            .unwrap()
+            .identity()
+            .clone();
+        env.verify_signed(&[trusted]).unwrap();
+
+        ph_buffer_free(&mut json);
+        ph_request_free(req);
+    }
+}
+
+#[test]
+fn errors_mirror_handshake_errors() {
+    unsafe {
+        let mut req = ptr::null_mut();
+        let bad = b"{not json";
+        assert_eq!(
+            ph_request_new(bad.as_ptr(), bad.len(), &mut req),
+            PhStatus::BadJson
+        );
+        assert!(req.is_null());
+
+        let env = RequestEnvelope::new(serde_json::from_str(INTENT).unwrap()).unwrap();
+        let mut tampered = env.clone();
+        tampered.intent.nonce += 1;
+        let json = serde_json::to_vec(&tampered).unwrap();
+        assert_eq!(
+            ph_request_from_json(json.as_ptr(), json.len(), &mut req),
+            PhStatus::ChecksumMismatch
+        );
+
+        let json = br#"{"max_steps":0}"#;
+        assert_eq!(
+            ph_constraints_validate(json.as_ptr(), json.len()),
+            PhStatus::InvalidConstraints
+        );
+
+        assert_eq!(ph_request_verify(ptr::null()), PhStatus::NullArgument);
+        assert_eq!(
+            ph_request_new(INTENT.as_ptr(), INTENT.len(), ptr::null_mut()),
+            PhStatus::NullArgument
+        );
+    }
+}
+
+#[test]
+fn response_builds_and_roundtrips() {
+    unsafe {
+        let id = "intent-ffi-1";
+        let msg = "Accepted.";
+        let mut resp = ptr::null_mut();
+        assert_eq!(
+            ph_response_new(
+                id.as_ptr(),
+                id.len(),
+                PhRunStatus::Accepted as u32,
+                msg.as_ptr(),
+                msg.len(),
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                &mut resp
+            ),
+            PhStatus::Ok
+        );
+        assert_eq!(ph_response_verify(resp), PhStatus::Ok);
+
+        let mut json = PhBuffer::EMPTY;
+        assert_eq!(ph_response_to_json(resp, &mut json), PhStatus::Ok);
+        let mut decoded = ptr::null_mut();
+        assert_eq!(
+            ph_response_from_json(json.data, json.len, &mut decoded),
+            PhStatus::Ok
+        );
+
+        ph_buffer_free(&mut json);
+        ph_response_free(decoded);
+        ph_response_free(resp);
+    }
+}
+
+#[test]
+fn response_carries_payload_and_logs() {
+    let payload = RunPayload::new(
+        RunResult {
+            cartridge_id: "counter_v1".to_string(),
+            outputs: vec![Datum::new("count", 1)],
+        },
+        ReceiptPayload {
+            run_id: "run-ffi-1".to_string(),
+            intent_statement: "Seal from C.".to_string(),
+            final_trace_hash: "ab".repeat(32),
+            steps: 1,
+            merkle_root: "cd".repeat(32),
+            salted: false,
+        },
+    );
+    let logs = vec![StepLog {
+        index: 0,
+        name: "tick-0".to_string(),
+        checksum_hex: "ef".repeat(32),
+        len: 12,
+    }];
+    let payload_json = serde_json::to_vec(&payload).unwrap();
+    let logs_json = serde_json::to_vec(&logs).unwrap();
+    let id = "intent-ffi-1";
+    let msg = "Completed.";
+    unsafe {
+        let mut resp = ptr::null_mut();
+        assert_eq!(
+            ph_response_new(
+                id.as_ptr(),
+                id.len(),
+                PhRunStatus::Completed as u32,
+                msg.as_ptr(),
+                msg.len(),
+                payload_json.as_ptr(),
+                payload_json.len(),
+                logs_json.as_ptr(),
+                logs_json.len(),
+                &mut resp
+            ),
+            PhStatus::Ok
+        );
+        assert_eq!(ph_response_verify(resp), PhStatus::Ok);
+
+        let mut json = PhBuffer::EMPTY;
+        assert_eq!(ph_response_to_json(resp, &mut json), PhStatus::Ok);
+        let env: ResponseEnvelope = serde_json::from_slice(json.as_slice()).unwrap();
+        env.verify().unwrap();
+        assert_eq!(env.payload, Some(payload));
+        assert_eq!(env.logs, Some(logs));
+
+        let bad = b"[{\"index\":0}]";
+        let mut rejected = ptr::null_mut();
+        assert_eq!(
+            ph_response_new(
+                id.as_ptr(),
+                id.len(),
+                PhRunStatus::Completed as u32,
+                msg.as_ptr(),
+                msg.len(),
+                ptr::null(),
+                0,
+                bad.as_ptr(),
+                bad.len(),
+                &mut rejected
+            ),
+            PhStatus::BadJson
+        );
+        assert!(rejected.is_null());
+
+        ph_buffer_free(&mut json);
+        ph_response_free(resp);
+    }
+}
+
+#[test]
+fn out_of_range_enum_arguments_are_rejected() {
+    let id = "intent-ffi-1";
+    unsafe {
+        let mut resp = ptr::null_mut();
+        assert_eq!(
+            ph_response_new(
+                id.as_ptr(),
+                id.len(),
+                6,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+                &mut resp
+            ),
+            PhStatus::InvalidArgument
+        );
+        assert!(resp.is_null());
+    }
+
+    let name = unsafe { CStr::from_ptr(ph_status_name(7777)) };
+    assert_eq!(name.to_str().unwrap(), "INVALID_ARGUMENT");
+}
+
+#[test]
+fn status_names_are_static_c_strings() {
+    let name = unsafe { CStr::from_ptr(ph_status_name(PhStatus::ChecksumMismatch as u32)) };
+    assert_eq!(name.to_str().unwrap(), "CHECKSUM_MISMATCH");
+    for code in [0, 18, 100, 104] {
+        let status = PhStatus::try_from(code).unwrap();
+        assert_eq!(status as u32, code);
+    }
+}
+
+#[test]
+fn request_roundtrips_through_cbor() {
+    unsafe {
+        let req = new_request();
+        let mut cbor = PhBuffer::EMPTY;
+        assert_eq!(ph_request_to_cbor(req, &mut cbor), PhStatus::Ok);
+
+        let mut decoded = ptr::null_mut();
+        assert_eq!(
+            ph_request_from_cbor(cbor.data, cbor.len, &mut decoded),
+            PhStatus::Ok
+        );
+        assert_eq!(
+            ph_request_from_cbor(cbor.data, 1, &mut decoded),
+            PhStatus::BadCbor
+        );
+
+        ph_buffer_free(&mut cbor);
+        ph_request_free(decoded);
+        ph_request_free(req);
+    }
+}
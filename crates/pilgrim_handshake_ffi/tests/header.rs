@@ -0,0 +1,30 @@
+//! The checked-in C header must match the `extern "C"` surface.
+//!
+//! Regenerate with:
+//! `PH_REGEN_HEADER=1 cargo test -p pilgrim_handshake_ffi --test header`
+
+use std::path::Path;
+
+#[test]
+fn header_is_up_to_date() {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(crate_dir)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+
+    let path = crate_dir.join("include/pilgrim_handshake.h");
+    if std::env::var_os("PH_REGEN_HEADER").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+        return;
+    }
+    let checked_in = std::fs::read(&path).unwrap();
+    assert!(
+        checked_in == generated,
+        "include/pilgrim_handshake.h is stale; regenerate with PH_REGEN_HEADER=1"
+    );
+}
@@ -1,16 +1,20 @@
-// pilgrim_identity v0.1.0 (Stub C)
-// Deterministic "identity proof" flow.
-// NOT cryptographically secure yet, but:
-// - stable interfaces
-// - deterministic fingerprints + proofs
-// - can be swapped for ed25519 signatures later with no API break
+// pilgrim_identity v0.2.0
+// Deterministic identity proof flow.
+// - Ed25519 signatures: `Keypair::prove` signs, `Identity::verify` needs only the public key
+// - stable interfaces: `Identity::new` / `subject_id` unchanged for Console + Mandate
+// - legacy Stub C hashing proofs (NOT cryptographically secure): `Identity::prove` is
+//   deprecated, and `verify` only accepts them with the `stub` feature
 
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, IdentityError>;
 
+/// Domain separation prefix for signed identity proofs.
+const PROOF_CONTEXT: &str = "pilgrim-identity/v1";
+
 #[derive(Debug, Error)]
 pub enum IdentityError {
     #[error("invalid subject id")]
@@ -19,24 +23,40 @@ pub enum IdentityError {
     InvalidPublicKey,
     #[error("proof verification failed")]
     VerificationFailed,
+    #[error("proof scheme not enabled in this build")]
+    UnsupportedScheme,
+}
+
+/// How a proof was produced.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProofScheme {
+    Ed25519,
+    /// Stub C: SHA-256(pubkey_fingerprint || ":" || message_hash). Forgeable.
+    StubSha256,
 }
 
-/// Minimal identity record.
-/// In Stub C, we treat `pubkey_bytes` as an arbitrary byte-string.
+/// Minimal identity record (public data only).
 /// Fingerprint is SHA-256(pubkey_bytes) hex.
+/// For Ed25519 proofs, `pubkey_hex` must be a 32-byte Ed25519 public key.
+/// Records from v0.1 have no `pubkey_hex`; it reads back empty.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Identity {
     pub subject_id: String,
     pub pubkey_fingerprint: String,
+    #[serde(default)]
+    pub pubkey_hex: String,
 }
 
-/// Proof that an identity "authorized" an action.
-/// Stub C uses deterministic hashing instead of real signatures.
+/// Proof that an identity authorized an action.
+/// `proof_hash` holds the hex signature (or the stub hash).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct IdentityProof {
     pub subject_id: String,
     pub pubkey_fingerprint: String,
     pub message_hash: String,
+    /// Absent in v0.1 proofs, which were all Stub C.
+    #[serde(default = "legacy_scheme")]
+    pub scheme: ProofScheme,
     pub proof_hash: String,
 }
 
@@ -56,26 +76,29 @@ impl Identity {
         Ok(Self {
             subject_id,
             pubkey_fingerprint,
+            pubkey_hex: hex::encode(pubkey_bytes),
         })
     }
 
-    /// Produce a deterministic "proof" for a given message hash.
+    /// Produce a Stub C "proof" for a given message hash.
     ///
-    /// Stub C "signature":
-    /// proof_hash = SHA-256(pubkey_fingerprint || ":" || message_hash)
+    /// Anyone holding the public fingerprint can produce this, and `verify`
+    /// only accepts it with the `stub` feature enabled.
+    #[deprecated(since = "0.2.0", note = "forgeable Stub C proof; use `Keypair::prove`")]
     pub fn prove(&self, message_hash: impl Into<String>) -> IdentityProof {
         let message_hash = message_hash.into();
-        let proof_hash = sha256_hex(format!("{}:{}", self.pubkey_fingerprint, message_hash).as_bytes());
+        let proof_hash = stub_proof_hash(&self.pubkey_fingerprint, &message_hash);
 
         IdentityProof {
             subject_id: self.subject_id.clone(),
             pubkey_fingerprint: self.pubkey_fingerprint.clone(),
             message_hash,
+            scheme: ProofScheme::StubSha256,
             proof_hash,
         }
     }
 
-    /// Verify a proof against this identity.
+    /// Verify a proof against this identity (public key only).
     pub fn verify(&self, proof: &IdentityProof) -> Result<()> {
         if proof.subject_id != self.subject_id {
             return Err(IdentityError::VerificationFailed);
@@ -84,15 +107,112 @@ impl Identity {
             return Err(IdentityError::VerificationFailed);
         }
 
-        let expected = sha256_hex(format!("{}:{}", self.pubkey_fingerprint, proof.message_hash).as_bytes());
-        if expected != proof.proof_hash {
-            return Err(IdentityError::VerificationFailed);
+        match proof.scheme {
+            ProofScheme::Ed25519 => {
+                let key = self.verifying_key()?;
+                let sig_bytes: [u8; 64] = hex::decode(&proof.proof_hash)
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or(IdentityError::VerificationFailed)?;
+                let message = signed_message(&self.subject_id, &proof.message_hash);
+
+                key.verify_strict(message.as_bytes(), &Signature::from_bytes(&sig_bytes))
+                    .map_err(|_| IdentityError::VerificationFailed)
+            }
+            #[cfg(feature = "stub")]
+            ProofScheme::StubSha256 => {
+                let expected = stub_proof_hash(&self.pubkey_fingerprint, &proof.message_hash);
+                if expected != proof.proof_hash {
+                    return Err(IdentityError::VerificationFailed);
+                }
+                Ok(())
+            }
+            #[cfg(not(feature = "stub"))]
+            ProofScheme::StubSha256 => Err(IdentityError::UnsupportedScheme),
+        }
+    }
+
+    fn verifying_key(&self) -> Result<VerifyingKey> {
+        let bytes: [u8; 32] = hex::decode(&self.pubkey_hex)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or(IdentityError::InvalidPublicKey)?;
+        VerifyingKey::from_bytes(&bytes).map_err(|_| IdentityError::InvalidPublicKey)
+    }
+}
+
+/// Ed25519 signing identity: the secret half that can `prove`.
+pub struct Keypair {
+    identity: Identity,
+    signing_key: SigningKey,
+}
+
+impl Keypair {
+    /// Generate a fresh keypair from the OS RNG.
+    pub fn generate(subject_id: impl Into<String>) -> Result<Self> {
+        let signing_key = SigningKey::generate(&mut rand_core::OsRng);
+        Self::from_signing_key(subject_id, signing_key)
+    }
+
+    /// Restore a keypair from its 32-byte secret.
+    pub fn from_secret_bytes(subject_id: impl Into<String>, secret: &[u8; 32]) -> Result<Self> {
+        Self::from_signing_key(subject_id, SigningKey::from_bytes(secret))
+    }
+
+    fn from_signing_key(subject_id: impl Into<String>, signing_key: SigningKey) -> Result<Self> {
+        let identity = Identity::new(subject_id, signing_key.verifying_key().as_bytes())?;
+        Ok(Self {
+            identity,
+            signing_key,
+        })
+    }
+
+    /// Public identity (safe to share).
+    pub fn identity(&self) -> &Identity {
+        &self.identity
+    }
+
+    /// 32-byte secret, for sealed storage only.
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.signing_key.to_bytes()
+    }
+
+    /// Sign a message hash.
+    pub fn prove(&self, message_hash: impl Into<String>) -> IdentityProof {
+        let message_hash = message_hash.into();
+        let message = signed_message(&self.identity.subject_id, &message_hash);
+        let signature = self.signing_key.sign(message.as_bytes());
+
+        IdentityProof {
+            subject_id: self.identity.subject_id.clone(),
+            pubkey_fingerprint: self.identity.pubkey_fingerprint.clone(),
+            message_hash,
+            scheme: ProofScheme::Ed25519,
+            proof_hash: hex::encode(signature.to_bytes()),
         }
+    }
+}
 
-        Ok(())
+impl std::fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keypair")
+            .field("identity", &self.identity)
+            .finish_non_exhaustive()
     }
 }
 
+fn signed_message(subject_id: &str, message_hash: &str) -> String {
+    format!("{}:{}:{}", PROOF_CONTEXT, subject_id, message_hash)
+}
+
+fn legacy_scheme() -> ProofScheme {
+    ProofScheme::StubSha256
+}
+
+fn stub_proof_hash(pubkey_fingerprint: &str, message_hash: &str) -> String {
+    sha256_hex(format!("{}:{}", pubkey_fingerprint, message_hash).as_bytes())
+}
+
 /// Utility: SHA-256 hex string
 pub fn sha256_hex(bytes: &[u8]) -> String {
     let mut h = Sha256::new();
@@ -104,6 +224,10 @@ pub fn sha256_hex(bytes: &[u8]) -> String {
 mod tests {
     use super::*;
 
+    fn demo_keypair() -> Keypair {
+        Keypair::from_secret_bytes("ernesto_lopez", &[9; 32]).unwrap()
+    }
+
     #[test]
     fn identity_fingerprint_is_stable() {
         let id1 = Identity::new("ernesto_lopez", b"demo-pubkey-bytes").unwrap();
@@ -113,19 +237,80 @@ mod tests {
 
     #[test]
     fn proof_verifies() {
-        let identity = Identity::new("ernesto_lopez", b"demo-pubkey-bytes").unwrap();
+        let keypair = demo_keypair();
         let msg_hash = sha256_hex(b"hello-world");
-        let proof = identity.prove(msg_hash);
-        identity.verify(&proof).unwrap();
+        let proof = keypair.prove(msg_hash);
+        keypair.identity().verify(&proof).unwrap();
     }
 
     #[test]
     fn proof_fails_on_tamper() {
+        let keypair = demo_keypair();
+        let msg_hash = sha256_hex(b"hello-world");
+        let mut proof = keypair.prove(msg_hash);
+
+        proof.message_hash = sha256_hex(b"goodbye-world");
+        assert!(keypair.identity().verify(&proof).is_err());
+    }
+
+    #[test]
+    fn public_fingerprint_cannot_forge_proof() {
+        let identity = demo_keypair().identity().clone();
+        let msg_hash = sha256_hex(b"hello-world");
+
+        // Everything a Stub C forger would know, dressed up as Ed25519
+        let forged = IdentityProof {
+            subject_id: identity.subject_id.clone(),
+            pubkey_fingerprint: identity.pubkey_fingerprint.clone(),
+            message_hash: msg_hash.clone(),
+            scheme: ProofScheme::Ed25519,
+            proof_hash: stub_proof_hash(&identity.pubkey_fingerprint, &msg_hash),
+        };
+        assert!(identity.verify(&forged).is_err());
+    }
+
+    #[test]
+    fn keypair_restores_from_secret() {
+        let keypair = Keypair::generate("ernesto_lopez").unwrap();
+        let restored = Keypair::from_secret_bytes("ernesto_lopez", &keypair.secret_bytes()).unwrap();
+        assert_eq!(keypair.identity(), restored.identity());
+    }
+
+    #[cfg(feature = "stub")]
+    #[test]
+    #[allow(deprecated)]
+    fn stub_proof_verifies() {
+        let identity = Identity::new("ernesto_lopez", b"demo-pubkey-bytes").unwrap();
+        let proof = identity.prove(sha256_hex(b"hello-world"));
+        identity.verify(&proof).unwrap();
+    }
+
+    #[cfg(not(feature = "stub"))]
+    #[test]
+    fn stub_proof_rejected_without_feature() {
         let identity = Identity::new("ernesto_lopez", b"demo-pubkey-bytes").unwrap();
         let msg_hash = sha256_hex(b"hello-world");
-        let mut proof = identity.prove(msg_hash);
+        let proof = IdentityProof {
+            subject_id: identity.subject_id.clone(),
+            pubkey_fingerprint: identity.pubkey_fingerprint.clone(),
+            proof_hash: stub_proof_hash(&identity.pubkey_fingerprint, &msg_hash),
+            message_hash: msg_hash,
+            scheme: ProofScheme::StubSha256,
+        };
+        assert!(matches!(identity.verify(&proof), Err(IdentityError::UnsupportedScheme)));
+    }
+
+    #[test]
+    fn v0_1_records_still_deserialize() {
+        let identity: Identity =
+            serde_json::from_str(r#"{"subject_id":"ernesto_lopez","pubkey_fingerprint":"ab"}"#)
+                .unwrap();
+        assert_eq!(identity.pubkey_hex, "");
 
-        proof.proof_hash = "deadbeef".to_string();
-        assert!(identity.verify(&proof).is_err());
+        let proof: IdentityProof = serde_json::from_str(
+            r#"{"subject_id":"ernesto_lopez","pubkey_fingerprint":"ab","message_hash":"cd","proof_hash":"ef"}"#,
+        )
+        .unwrap();
+        assert_eq!(proof.scheme, ProofScheme::StubSha256);
     }
 }
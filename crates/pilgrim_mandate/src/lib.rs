@@ -1,5 +1,13 @@
+use amethyst_invariants::{Enforced, EnforcementPoint};
 use pilgrim_identity::Identity;
 
+const ENFORCE: EnforcementPoint =
+    EnforcementPoint::new("SAFE_001", "pilgrim_mandate::Mandate::enforce");
+
+/// System invariants upheld by mandate enforcement.
+/// Unlisted identities are denied: fail closed, never assume.
+pub const ENFORCEMENT: &[EnforcementPoint] = &[ENFORCE];
+
 /// A single authorization rule
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MandateRule {
@@ -57,6 +65,14 @@ pub enum MandateError {
     },
 }
 
+impl Enforced for MandateError {
+    fn enforcement_point(&self) -> &'static EnforcementPoint {
+        match self {
+            MandateError::Denied { .. } => &ENFORCE,
+        }
+    }
+}
+
 impl std::fmt::Display for MandateError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,101 @@
+//! Built-in invariant checks, one family per `InvariantClass`.
+//!
+//! Fields are addressed with JSON pointers (RFC 6901) into the canonical
+//! serialization of the state, e.g. `"/value"` or `"/stage/name"`.
+//! Numeric checks are integer-only — no floats in enforcement paths.
+
+use crate::invariants::InvariantCheck;
+use serde_json::Value;
+
+/// Schema: the shape of the state (object keys and value kinds) must not change.
+pub fn schema() -> impl InvariantCheck {
+    |before: &Value, after: &Value| match shape_diff(before, after, "") {
+        Some(at) => Err(format!("schema changed at {at}")),
+        None => Ok(()),
+    }
+}
+
+/// Value: the integer at `pointer` must lie in `min..=max` after execution.
+pub fn value_range(pointer: &'static str, min: i64, max: i64) -> impl InvariantCheck {
+    move |_: &Value, after: &Value| {
+        let v = integer_at(after, pointer)?;
+        if v < min as i128 || v > max as i128 {
+            return Err(format!("{pointer} = {v} outside {min}..={max}"));
+        }
+        Ok(())
+    }
+}
+
+/// Transition: the value at `pointer` may only move along `allowed` edges.
+/// Staying in place is always allowed.
+pub fn transition(
+    pointer: &'static str,
+    allowed: &'static [(&'static str, &'static str)],
+) -> impl InvariantCheck {
+    move |before: &Value, after: &Value| {
+        let from = label_at(before, pointer)?;
+        let to = label_at(after, pointer)?;
+        if from == to || allowed.iter().any(|(a, b)| *a == from && *b == to) {
+            return Ok(());
+        }
+        Err(format!("{pointer}: transition {from} -> {to} not allowed"))
+    }
+}
+
+/// Temporal: the integer at `pointer` must never decrease.
+pub fn monotonic(pointer: &'static str) -> impl InvariantCheck {
+    move |before: &Value, after: &Value| {
+        let from = integer_at(before, pointer)?;
+        let to = integer_at(after, pointer)?;
+        if to < from {
+            return Err(format!("{pointer} went backwards: {from} -> {to}"));
+        }
+        Ok(())
+    }
+}
+
+fn integer_at(state: &Value, pointer: &str) -> Result<i128, String> {
+    let v = state
+        .pointer(pointer)
+        .ok_or_else(|| format!("{pointer} missing"))?;
+    v.as_i64()
+        .map(i128::from)
+        .or_else(|| v.as_u64().map(i128::from))
+        .ok_or_else(|| format!("{pointer} is not an integer"))
+}
+
+fn label_at(state: &Value, pointer: &str) -> Result<String, String> {
+    match state.pointer(pointer) {
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(other) => Ok(other.to_string()),
+        None => Err(format!("{pointer} missing")),
+    }
+}
+
+/// First path at which `a` and `b` differ in shape, if any, quoted and
+/// followed by the array lengths when those differ.
+fn shape_diff(a: &Value, b: &Value, path: &str) -> Option<String> {
+    match (a, b) {
+        (Value::Object(x), Value::Object(y)) => {
+            if x.len() != y.len() || x.keys().zip(y.keys()).any(|(k1, k2)| k1 != k2) {
+                return Some(format!("'{path}'"));
+            }
+            x.iter()
+                .find_map(|(k, v)| shape_diff(v, &y[k], &format!("{path}/{k}")))
+        }
+        (Value::Array(x), Value::Array(y)) => {
+            if x.len() != y.len() {
+                return Some(format!("'{path}': length {} -> {}", x.len(), y.len()));
+            }
+            x.iter()
+                .zip(y.iter())
+                .enumerate()
+                .find_map(|(i, (v, w))| shape_diff(v, w, &format!("{path}/{i}")))
+        }
+        (Value::Null, Value::Null)
+        | (Value::Bool(_), Value::Bool(_))
+        | (Value::Number(_), Value::Number(_))
+        | (Value::String(_), Value::String(_)) => None,
+        _ => Some(format!("'{path}'")),
+    }
+}
@@ -1,45 +1,154 @@
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Domain assigned to specs that do not declare one.
+pub const GLOBAL_DOMAIN: &str = "global";
+
+/// Canonical invariant classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum InvariantClass {
     Schema,
     Value,
-    Transition,
+    Distribution,
     Temporal,
+    Transition,
+}
+
+impl InvariantClass {
+    /// Stable ledger label
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            InvariantClass::Schema => "schema",
+            InvariantClass::Value => "value",
+            InvariantClass::Distribution => "distribution",
+            InvariantClass::Temporal => "temporal",
+            InvariantClass::Transition => "transition",
+        }
+    }
+}
+
+/// Executable predicate over the canonical (serialized) before/after state.
+///
+/// `Err` carries a human-readable reason; it is surfaced in `SentinelError`.
+/// Built-in checks for each class live in `crate::checks`.
+pub trait InvariantCheck: Send + Sync {
+    fn check(&self, before: &Value, after: &Value) -> Result<(), String>;
 }
 
-#[derive(Debug, Clone)]
+impl<F> InvariantCheck for F
+where
+    F: Fn(&Value, &Value) -> Result<(), String> + Send + Sync,
+{
+    fn check(&self, before: &Value, after: &Value) -> Result<(), String> {
+        self(before, after)
+    }
+}
+
+/// A declared invariant, optionally carrying its check.
+/// Specs without a check are declaration-only.
+#[derive(Clone)]
 pub struct InvariantSpec {
     pub id: &'static str,
     pub class: InvariantClass,
+    pub domain: &'static str,
+    pub check: Option<Arc<dyn InvariantCheck>>,
 }
 
 impl InvariantSpec {
     pub const fn new(id: &'static str, class: InvariantClass) -> Self {
-        Self { id, class }
+        Self {
+            id,
+            class,
+            domain: GLOBAL_DOMAIN,
+            check: None,
+        }
+    }
+
+    /// Scope this spec to a domain
+    pub const fn in_domain(mut self, domain: &'static str) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Attach the predicate this invariant enforces
+    pub fn with_check(mut self, check: impl InvariantCheck + 'static) -> Self {
+        self.check = Some(Arc::new(check));
+        self
     }
+
+    /// Evaluate the attached check. Declaration-only specs always pass.
+    pub fn evaluate(&self, before: &Value, after: &Value) -> Result<(), String> {
+        match &self.check {
+            Some(check) => check.check(before, after),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for InvariantSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InvariantSpec")
+            .field("id", &self.id)
+            .field("class", &self.class)
+            .field("domain", &self.domain)
+            .field("check", &self.check.is_some())
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RegistryError {
+    #[error("invariant already registered: {id}")]
+    Duplicate { id: &'static str },
 }
 
+/// Deterministic registry (in-memory, explicit).
+/// Iteration is always ordered by invariant ID.
 #[derive(Debug, Default)]
 pub struct InvariantRegistry {
-    specs: HashMap<&'static str, InvariantSpec>,
+    specs: BTreeMap<&'static str, InvariantSpec>,
 }
 
 impl InvariantRegistry {
     pub fn new() -> Self {
         Self {
-            specs: HashMap::new(),
+            specs: BTreeMap::new(),
         }
     }
 
-    pub fn register(&mut self, spec: InvariantSpec) {
+    /// Register a new invariant
+    /// Fails closed on duplicate ID — the existing spec is kept
+    pub fn register(&mut self, spec: InvariantSpec) -> Result<(), RegistryError> {
         if self.specs.contains_key(spec.id) {
-            panic!("Invariant already registered: {}", spec.id);
+            return Err(RegistryError::Duplicate { id: spec.id });
         }
         self.specs.insert(spec.id, spec);
+        Ok(())
     }
 
+    /// Fetch invariant by ID
     pub fn get(&self, id: &str) -> Option<&InvariantSpec> {
         self.specs.get(id)
     }
+
+    /// All invariants, ordered by ID
+    pub fn iter(&self) -> impl Iterator<Item = &InvariantSpec> {
+        self.specs.values()
+    }
+
+    /// All invariants for a domain, ordered by ID
+    pub fn by_domain<'a>(&'a self, domain: &'a str) -> impl Iterator<Item = &'a InvariantSpec> {
+        self.iter().filter(move |spec| spec.domain == domain)
+    }
+
+    pub fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
 }
@@ -1,39 +1,187 @@
-#[derive(Debug, Clone)]
+use crate::ledger_file::{LedgerFile, LedgerFileError};
+use pilgrim_dre::{enforce_with, HaltReason};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use thiserror::Error;
+
+/// A recorded drift violation.
+///
+/// Events are hash-chained: `event_hash` covers every other field,
+/// including `prev_event_hash`, so removing, reordering or editing a
+/// record breaks every link after it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DriftEvent {
-    pub domain: &'static str,
-    pub invariant: &'static str,
-    pub class: &'static str,
-    pub before_hash: &'static str,
-    pub after_hash: &'static str,
+    pub seq: u64,
+    pub domain: String,
+    pub invariant: String,
+    pub class: String,
+    pub before_hash: String,
+    pub after_hash: String,
+    pub prev_event_hash: Option<String>,
+    pub event_hash: String,
+}
+
+impl DriftEvent {
+    /// Recompute the hash this event should carry.
+    pub fn compute_hash(&self) -> String {
+        let to_hash = EventToHash {
+            seq: self.seq,
+            domain: &self.domain,
+            invariant: &self.invariant,
+            class: &self.class,
+            before_hash: &self.before_hash,
+            after_hash: &self.after_hash,
+            prev_event_hash: self.prev_event_hash.as_deref(),
+        };
+        let bytes = serde_json::to_vec(&to_hash).expect("serialization cannot fail");
+
+        let mut hasher = Sha256::new();
+        hasher.update(&bytes);
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Internal struct: explicitly defines what gets hashed (excludes `event_hash`).
+#[derive(Serialize)]
+struct EventToHash<'a> {
+    seq: u64,
+    domain: &'a str,
+    invariant: &'a str,
+    class: &'a str,
+    before_hash: &'a str,
+    after_hash: &'a str,
+    prev_event_hash: Option<&'a str>,
+}
+
+/// First broken link found by `DriftLedger::verify_chain`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ChainError {
+    #[error("ledger position {index}: expected seq {expected}, found {found}")]
+    SequenceGap { index: usize, expected: u64, found: u64 },
+    #[error("event {seq}: prev_event_hash does not match the preceding event")]
+    PrevHashMismatch {
+        seq: u64,
+        expected: Option<String>,
+        found: Option<String>,
+    },
+    #[error("event {seq}: event_hash does not match its contents")]
+    HashMismatch { seq: u64 },
 }
 
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct DriftLedger {
-    pub events: Vec<DriftEvent>,
+    events: Vec<DriftEvent>,
+    sink: Option<LedgerFile>,
 }
 
 impl DriftLedger {
     pub fn new() -> Self {
-        Self { events: Vec::new() }
+        Self {
+            events: Vec::new(),
+            sink: None,
+        }
     }
 
+    /// Open (or create) a file-backed ledger.
+    ///
+    /// Existing events are replayed and their chain verified; new events
+    /// continue the chain and are fsync'd before `record` returns.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LedgerFileError> {
+        let (sink, events) = LedgerFile::open(path)?;
+        Ok(Self {
+            events,
+            sink: Some(sink),
+        })
+    }
+
+    /// Record a violation.
+    /// FAILS CLOSED if an attached file sink cannot persist it.
     pub fn record(
         &mut self,
-        domain: &'static str,
-        invariant: &'static str,
-        class: &'static str,
-        before_hash: &'static str,
-        after_hash: &'static str,
+        domain: impl Into<String>,
+        invariant: impl Into<String>,
+        class: impl Into<String>,
+        before_hash: impl Into<String>,
+        after_hash: impl Into<String>,
     ) -> DriftEvent {
-        let event = DriftEvent {
-            domain,
-            invariant,
-            class,
-            before_hash,
-            after_hash,
+        match self.try_record(domain, invariant, class, before_hash, after_hash) {
+            Ok(event) => event,
+            Err(_) => enforce_with(HaltReason::LedgerWriteFailed),
+        }
+    }
+
+    /// Record a violation, reporting sink failures to the caller.
+    /// On failure nothing is added to the in-memory chain.
+    pub fn try_record(
+        &mut self,
+        domain: impl Into<String>,
+        invariant: impl Into<String>,
+        class: impl Into<String>,
+        before_hash: impl Into<String>,
+        after_hash: impl Into<String>,
+    ) -> Result<DriftEvent, LedgerFileError> {
+        let mut event = DriftEvent {
+            seq: self.events.len() as u64,
+            domain: domain.into(),
+            invariant: invariant.into(),
+            class: class.into(),
+            before_hash: before_hash.into(),
+            after_hash: after_hash.into(),
+            prev_event_hash: self.head().map(str::to_string),
+            event_hash: String::new(),
         };
+        event.event_hash = event.compute_hash();
+
+        // Durable FIRST — the in-memory chain never runs ahead of the file
+        if let Some(sink) = self.sink.as_mut() {
+            sink.append(&event)?;
+        }
 
         self.events.push(event.clone());
-        event
+        Ok(event)
+    }
+
+    /// Every recorded event, oldest first.
+    pub fn events(&self) -> &[DriftEvent] {
+        &self.events
     }
+
+    /// Hash of the most recent event (the chain head).
+    pub fn head(&self) -> Option<&str> {
+        self.events.last().map(|e| e.event_hash.as_str())
+    }
+
+    /// Walk the chain from genesis and report the first broken link.
+    pub fn verify_chain(&self) -> Result<(), ChainError> {
+        verify_events(&self.events)
+    }
+}
+
+/// Walk `events` from genesis and report the first broken link.
+pub fn verify_events(events: &[DriftEvent]) -> Result<(), ChainError> {
+    let mut prev: Option<&str> = None;
+
+    for (index, event) in events.iter().enumerate() {
+        if event.seq != index as u64 {
+            return Err(ChainError::SequenceGap {
+                index,
+                expected: index as u64,
+                found: event.seq,
+            });
+        }
+        if event.prev_event_hash.as_deref() != prev {
+            return Err(ChainError::PrevHashMismatch {
+                seq: event.seq,
+                expected: prev.map(str::to_string),
+                found: event.prev_event_hash.clone(),
+            });
+        }
+        if event.compute_hash() != event.event_hash {
+            return Err(ChainError::HashMismatch { seq: event.seq });
+        }
+        prev = Some(&event.event_hash);
+    }
+
+    Ok(())
 }
@@ -0,0 +1,140 @@
+//! Append-only, file-backed drift ledger.
+//!
+//! Format: JSON lines. One `DriftEvent` per line, `\n` terminated.
+//! Every append is fsync'd before it returns, so a record written by
+//! the sentinel is on disk before D.R.E. halts the process.
+//!
+//! A final line without its `\n` is a torn write (crash mid-append).
+//! It is reported as truncation, never silently dropped.
+
+use crate::ledger::{verify_events, ChainError, DriftEvent};
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LedgerFileError {
+    #[error("ledger I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("ledger line {line} is not a valid drift event")]
+    Corrupt { line: usize },
+    #[error("ledger truncated: {len} trailing bytes at offset {offset} form no complete event")]
+    Truncated { offset: u64, len: u64 },
+    #[error("ledger chain broken: {0}")]
+    Chain(#[from] ChainError),
+}
+
+/// Result of reading a ledger file without opening it for writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerReplay {
+    /// Every complete, chain-verified event in file order.
+    pub events: Vec<DriftEvent>,
+    /// Byte offset and length of a torn final write, if any.
+    pub truncated_tail: Option<(u64, u64)>,
+}
+
+/// Append handle on a ledger file.
+#[derive(Debug)]
+pub struct LedgerFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl LedgerFile {
+    /// Open (or create) the ledger at `path` for appending.
+    ///
+    /// Existing events are replayed and verified first.
+    /// FAILS CLOSED on a torn tail: the caller must inspect it with
+    /// `replay` before the file is written to again.
+    pub fn open(path: impl AsRef<Path>) -> Result<(Self, Vec<DriftEvent>), LedgerFileError> {
+        let path = path.as_ref().to_path_buf();
+
+        // create_new decides "fresh file" atomically; no exists() race
+        let (file, created) = match OpenOptions::new().append(true).create_new(true).open(&path) {
+            Ok(file) => (file, true),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                (OpenOptions::new().append(true).open(&path)?, false)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if created {
+            // Make the new directory entry itself durable
+            if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+                File::open(dir)?.sync_all()?;
+            }
+        }
+
+        let replay = Self::replay(&path)?;
+        if let Some((offset, len)) = replay.truncated_tail {
+            return Err(LedgerFileError::Truncated { offset, len });
+        }
+
+        Ok((Self { path, file }, replay.events))
+    }
+
+    /// Read every complete event from `path` and verify the chain.
+    ///
+    /// A torn final write is reported in `truncated_tail` rather than as an
+    /// error, so a post-mortem can still read the last complete violation.
+    pub fn replay(path: impl AsRef<Path>) -> Result<LedgerReplay, LedgerFileError> {
+        let mut bytes = Vec::new();
+        File::open(path.as_ref())?.read_to_end(&mut bytes)?;
+
+        let complete = bytes
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+
+        let truncated_tail = if complete < bytes.len() {
+            Some((complete as u64, (bytes.len() - complete) as u64))
+        } else {
+            None
+        };
+
+        let mut events = Vec::new();
+        for (index, line) in bytes[..complete].split(|b| *b == b'\n').enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let event: DriftEvent = serde_json::from_slice(line)
+                .map_err(|_| LedgerFileError::Corrupt { line: index + 1 })?;
+            events.push(event);
+        }
+
+        verify_events(&events)?;
+
+        Ok(LedgerReplay {
+            events,
+            truncated_tail,
+        })
+    }
+
+    /// Append one event and fsync before returning.
+    ///
+    /// On failure the file is cut back to its previous length, so a
+    /// partial write does not leave a torn tail behind a live process.
+    pub fn append(&mut self, event: &DriftEvent) -> Result<(), LedgerFileError> {
+        let mut line = serde_json::to_vec(event).expect("serialization cannot fail");
+        line.push(b'\n');
+
+        let len = self.file.metadata()?.len();
+
+        // Single write: a crash can tear the line, never interleave it
+        let written = self
+            .file
+            .write_all(&line)
+            .and_then(|()| self.file.sync_data());
+        if let Err(e) = written {
+            // Best effort: if this fails too, open() reports the torn tail
+            let _ = self.file.set_len(len).and_then(|()| self.file.sync_data());
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
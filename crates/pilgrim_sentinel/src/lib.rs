@@ -1,7 +1,10 @@
+pub mod checks;
 pub mod invariants;
 pub mod ledger;
+pub mod ledger_file;
 pub mod sentinel;
 
 pub use invariants::*;
 pub use ledger::*;
+pub use ledger_file::*;
 pub use sentinel::*;
@@ -1,56 +1,236 @@
-use crate::invariants::{InvariantClass, InvariantSpec};
-use crate::ledger::DriftLedger;
-use pilgrim_dre::enforce;
+use crate::invariants::{InvariantRegistry, InvariantSpec};
+use amethyst_invariants::{Enforced, EnforcementPoint};
+use crate::ledger::{DriftEvent, DriftLedger};
+use pilgrim_dre::{enforce_with, HaltReason};
 use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
 
-#[derive(Debug)]
+/// Content fingerprint of a state snapshot.
+/// `hash` is the hex SHA-256 of the canonical serialization;
+/// `snapshot` is that serialization, kept for executable invariants.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SentinelToken {
-    pub hash: &'static str,
+    pub hash: String,
+    pub snapshot: Value,
+}
+
+/// What the sentinel does once a violation has been recorded.
+///
+/// `Abort` is the production default: the ledger entry is written,
+/// then D.R.E. halts the process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SentinelPolicy {
+    #[default]
+    Abort,
+    Panic,
+    Return,
+}
+
+// Silent state mutation is exactly what TRANS_002 forbids
+const CHECK: EnforcementPoint =
+    EnforcementPoint::new("TRANS_002", "pilgrim_sentinel::Sentinel::check");
+// State that cannot be fingerprinted cannot be inspected either
+const TRY_BEFORE: EnforcementPoint =
+    EnforcementPoint::new("TRANS_002", "pilgrim_sentinel::Sentinel::try_before");
+// A failed domain invariant stops the run instead of carrying on
+const CHECK_DOMAIN: EnforcementPoint =
+    EnforcementPoint::new("SAFE_001", "pilgrim_sentinel::Sentinel::check_domain");
+
+/// System invariants upheld by the sentinel.
+pub const ENFORCEMENT: &[EnforcementPoint] = &[CHECK, TRY_BEFORE, CHECK_DOMAIN];
+
+#[derive(Debug, Clone, Error)]
+pub enum SentinelError {
+    #[error("drift detected in {} (invariant {}): {} -> {}", .0.domain, .0.invariant, .0.before_hash, .0.after_hash)]
+    Drift(Box<DriftEvent>),
+    #[error("invariant {} violated in {}: {reason}", .event.invariant, .event.domain)]
+    Violated {
+        event: Box<DriftEvent>,
+        reason: String,
+    },
+    #[error("state is not serializable; cannot fingerprint")]
+    Unserializable,
+}
+
+impl Enforced for SentinelError {
+    fn enforcement_point(&self) -> &'static EnforcementPoint {
+        match self {
+            SentinelError::Drift(_) => &CHECK,
+            SentinelError::Violated { .. } => &CHECK_DOMAIN,
+            SentinelError::Unserializable => &TRY_BEFORE,
+        }
+    }
+}
+
+impl SentinelError {
+    /// Reason written to the D.R.E. halt record.
+    pub fn halt_reason(&self) -> HaltReason {
+        match self {
+            SentinelError::Drift(_) => HaltReason::SentinelDrift,
+            SentinelError::Violated { .. } => HaltReason::InvariantViolated,
+            SentinelError::Unserializable => HaltReason::Unserializable,
+        }
+    }
 }
 
 pub struct Sentinel;
 
 impl Sentinel {
     /// Capture pre-execution state fingerprint
-    pub fn before<T: Serialize>(
-        _state: &T,
-        hash: &'static str,
-    ) -> SentinelToken {
-        SentinelToken { hash }
+    /// FAILS CLOSED if the state cannot be fingerprinted
+    pub fn before<T: Serialize>(state: &T) -> SentinelToken {
+        match Self::try_before(state) {
+            Ok(token) => token,
+            Err(err) => enforce_with(err.halt_reason()),
+        }
+    }
+
+    /// Capture pre-execution state fingerprint, reporting failure to the caller
+    pub fn try_before<T: Serialize>(state: &T) -> Result<SentinelToken, SentinelError> {
+        snapshot(state)
     }
 
     /// Deterministic Runtime Enforcement
     /// FAILS CLOSED — NO RECOVERY PATH
     pub fn after<T: Serialize>(
         before: &SentinelToken,
-        _after_state: &T,
+        after_state: &T,
         domain: &'static str,
         spec: &InvariantSpec,
         ledger: &mut DriftLedger,
     ) -> SentinelToken {
-        let after_hash = core::any::type_name::<T>();
+        match Self::after_with(SentinelPolicy::Abort, before, after_state, domain, spec, ledger) {
+            Ok(token) => token,
+            // Unreachable: the Abort policy never returns an error
+            Err(err) => enforce_with(err.halt_reason()),
+        }
+    }
+
+    /// Enforcement under an explicit policy.
+    /// The violation is always recorded before the policy is applied.
+    pub fn after_with<T: Serialize>(
+        policy: SentinelPolicy,
+        before: &SentinelToken,
+        after_state: &T,
+        domain: &'static str,
+        spec: &InvariantSpec,
+        ledger: &mut DriftLedger,
+    ) -> Result<SentinelToken, SentinelError> {
+        apply(policy, Self::check(before, after_state, domain, spec, ledger))
+    }
+
+    /// Compare `after_state` against `before` and record any drift.
+    /// Never halts — the caller decides what happens next.
+    pub fn check<T: Serialize>(
+        before: &SentinelToken,
+        after_state: &T,
+        domain: &'static str,
+        spec: &InvariantSpec,
+        ledger: &mut DriftLedger,
+    ) -> Result<SentinelToken, SentinelError> {
+        let after = snapshot(after_state)?;
 
         // 🔒 D.R.E. — silent runtime mutation is forbidden
-        if before.hash != after_hash {
+        if before.hash != after.hash {
             // Record violation FIRST (immutable receipt)
-            ledger.record(
+            let event = ledger.record(
                 domain,
                 spec.id,
-                match spec.class {
-                    InvariantClass::Schema => "schema",
-                    InvariantClass::Value => "value",
-                    InvariantClass::Transition => "transition",
-                    InvariantClass::Temporal => "temporal",
-                },
-                before.hash,
-                after_hash,
+                spec.class.as_str(),
+                before.hash.clone(),
+                after.hash,
             );
 
-            // HARD STOP — deterministic halt
-            // This NEVER returns
-            enforce(false);
+            return Err(SentinelError::Drift(Box::new(event)));
         }
 
-        SentinelToken { hash: before.hash }
+        Ok(after)
+    }
+
+    /// Domain enforcement under an explicit policy.
+    pub fn after_domain_with<T: Serialize>(
+        policy: SentinelPolicy,
+        before: &SentinelToken,
+        after_state: &T,
+        domain: &'static str,
+        registry: &InvariantRegistry,
+        ledger: &mut DriftLedger,
+    ) -> Result<SentinelToken, SentinelError> {
+        apply(policy, Self::check_domain(before, after_state, domain, registry, ledger))
     }
+
+    /// Evaluate every registered invariant for `domain` (in ID order).
+    ///
+    /// Unlike `check`, the state may change — only the invariants decide.
+    /// Every failing invariant is recorded; the first one is returned.
+    pub fn check_domain<T: Serialize>(
+        before: &SentinelToken,
+        after_state: &T,
+        domain: &'static str,
+        registry: &InvariantRegistry,
+        ledger: &mut DriftLedger,
+    ) -> Result<SentinelToken, SentinelError> {
+        let after = snapshot(after_state)?;
+        let mut first: Option<SentinelError> = None;
+
+        for spec in registry.by_domain(domain) {
+            if let Err(reason) = spec.evaluate(&before.snapshot, &after.snapshot) {
+                let event = ledger.record(
+                    domain,
+                    spec.id,
+                    spec.class.as_str(),
+                    before.hash.clone(),
+                    after.hash.clone(),
+                );
+                first.get_or_insert(SentinelError::Violated {
+                    event: Box::new(event),
+                    reason,
+                });
+            }
+        }
+
+        match first {
+            Some(err) => Err(err),
+            None => Ok(after),
+        }
+    }
+}
+
+fn apply(
+    policy: SentinelPolicy,
+    result: Result<SentinelToken, SentinelError>,
+) -> Result<SentinelToken, SentinelError> {
+    let err = match result {
+        Ok(token) => return Ok(token),
+        Err(err) => err,
+    };
+
+    match policy {
+        // HARD STOP — deterministic halt
+        SentinelPolicy::Abort => enforce_with(err.halt_reason()),
+        SentinelPolicy::Panic => panic!("PILGRIM SENTINEL: {err}"),
+        SentinelPolicy::Return => Err(err),
+    }
+}
+
+/// Canonical fingerprint: SHA-256 over the JSON serialization of `state`.
+///
+/// The state is first lowered to a `serde_json::Value`, whose object keys are
+/// ordered, so map iteration order cannot change the digest.
+pub fn fingerprint<T: Serialize>(state: &T) -> Result<String, SentinelError> {
+    snapshot(state).map(|token| token.hash)
+}
+
+fn snapshot<T: Serialize>(state: &T) -> Result<SentinelToken, SentinelError> {
+    let value = serde_json::to_value(state).map_err(|_| SentinelError::Unserializable)?;
+    let bytes = serde_json::to_vec(&value).map_err(|_| SentinelError::Unserializable)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    Ok(SentinelToken {
+        hash: hex::encode(hasher.finalize()),
+        snapshot: value,
+    })
 }
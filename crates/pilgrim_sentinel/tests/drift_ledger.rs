@@ -1,4 +1,4 @@
-use pilgrim_sentinel::ledger::DriftLedger;
+use pilgrim_sentinel::ledger::{verify_events, ChainError, DriftEvent, DriftLedger};
 
 #[test]
 fn drift_ledger_chains_events_deterministically() {
@@ -13,7 +13,7 @@ fn drift_ledger_chains_events_deterministically() {
     );
 
     // Explicitly end the first borrow by extracting what we need
-    let e1_hash = e1.after_hash.clone();
+    let e1_hash = e1.event_hash.clone();
 
     let e2 = ledger.record(
         "inv-002",
@@ -23,5 +23,57 @@ fn drift_ledger_chains_events_deterministically() {
         "hash-B",
     );
 
-assert_eq!(e2.prev_event_hash.as_deref(), Some(&e1_hash));
+    assert_eq!(e1.prev_event_hash, None);
+    assert_eq!(e2.prev_event_hash.as_deref(), Some(e1_hash.as_str()));
+    assert_eq!((e1.seq, e2.seq), (0, 1));
+    assert_eq!(ledger.verify_chain(), Ok(()));
+}
+
+fn events_of(n: usize) -> Vec<DriftEvent> {
+    let mut ledger = DriftLedger::new();
+    for i in 0..n {
+        ledger.record("demo", format!("inv-{i}"), "value", "before", "after");
+    }
+    ledger.events().to_vec()
+}
+
+#[test]
+fn verify_chain_detects_removed_event() {
+    let mut events = events_of(3);
+    events.remove(1);
+
+    assert!(matches!(
+        verify_events(&events),
+        Err(ChainError::SequenceGap { index: 1, expected: 1, found: 2 })
+    ));
+}
+
+#[test]
+fn verify_chain_detects_reordered_events() {
+    let mut events = events_of(3);
+    events.swap(0, 1);
+
+    assert!(matches!(
+        verify_events(&events),
+        Err(ChainError::SequenceGap { index: 0, .. })
+    ));
+}
+
+#[test]
+fn verify_chain_detects_edited_event() {
+    let mut events = events_of(3);
+    events[2].after_hash = "forged".to_string();
+
+    assert_eq!(verify_events(&events), Err(ChainError::HashMismatch { seq: 2 }));
+}
+
+#[test]
+fn verify_chain_detects_relinked_event() {
+    let mut events = events_of(3);
+    events[1].prev_event_hash = None;
+
+    assert!(matches!(
+        verify_events(&events),
+        Err(ChainError::PrevHashMismatch { seq: 1, .. })
+    ));
 }
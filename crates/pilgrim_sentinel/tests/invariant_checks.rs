@@ -0,0 +1,114 @@
+use amethyst_invariants::Enforced;
+use pilgrim_sentinel::checks;
+use pilgrim_sentinel::ledger::DriftLedger;
+use pilgrim_sentinel::{InvariantClass, InvariantRegistry, InvariantSpec, Sentinel, SentinelError};
+
+#[derive(serde::Serialize)]
+struct Run {
+    stage: &'static str,
+    tick: u64,
+    load: i64,
+}
+
+const STAGES: &[(&str, &str)] = &[("Init", "Build"), ("Build", "Lock")];
+
+fn registry() -> InvariantRegistry {
+    let mut registry = InvariantRegistry::new();
+    for spec in [
+        InvariantSpec::new("run.schema", InvariantClass::Schema).with_check(checks::schema()),
+        InvariantSpec::new("run.load", InvariantClass::Value)
+            .with_check(checks::value_range("/load", 0, 100)),
+        InvariantSpec::new("run.stage", InvariantClass::Transition)
+            .with_check(checks::transition("/stage", STAGES)),
+        InvariantSpec::new("run.tick", InvariantClass::Temporal)
+            .with_check(checks::monotonic("/tick")),
+        // Declaration-only specs never fail
+        InvariantSpec::new("run.declared", InvariantClass::Distribution),
+    ] {
+        registry.register(spec.in_domain("run")).unwrap();
+    }
+    registry
+}
+
+fn evaluate(before: &Run, after: &Run, ledger: &mut DriftLedger) -> Result<(), SentinelError> {
+    let token = Sentinel::before(before);
+    Sentinel::check_domain(&token, after, "run", &registry(), ledger).map(|_| ())
+}
+
+#[test]
+fn legal_progress_passes_every_invariant() {
+    let mut ledger = DriftLedger::new();
+    let before = Run { stage: "Init", tick: 1, load: 10 };
+    let after = Run { stage: "Build", tick: 2, load: 40 };
+
+    evaluate(&before, &after, &mut ledger).unwrap();
+    assert!(ledger.events().is_empty());
+}
+
+#[test]
+fn failing_invariant_is_named_in_ledger() {
+    let mut ledger = DriftLedger::new();
+    let before = Run { stage: "Init", tick: 5, load: 10 };
+    let after = Run { stage: "Lock", tick: 4, load: 10 };
+
+    let err = evaluate(&before, &after, &mut ledger).unwrap_err();
+
+    assert_eq!(err.system_invariant(), "SAFE_001");
+
+    // First failure in ID order is surfaced
+    match err {
+        SentinelError::Violated { event, reason } => {
+            assert_eq!(event.invariant, "run.stage");
+            assert_eq!(reason, "/stage: transition Init -> Lock not allowed");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // Every failure is recorded
+    let failed: Vec<_> = ledger.events().iter().map(|e| e.invariant.as_str()).collect();
+    assert_eq!(failed, ["run.stage", "run.tick"]);
+    assert_eq!(ledger.events()[1].class, "temporal");
+}
+
+#[test]
+fn value_range_rejects_out_of_bounds() {
+    let mut ledger = DriftLedger::new();
+    let before = Run { stage: "Init", tick: 1, load: 10 };
+    let after = Run { stage: "Init", tick: 1, load: 101 };
+
+    evaluate(&before, &after, &mut ledger).unwrap_err();
+    assert_eq!(ledger.events()[0].invariant, "run.load");
+}
+
+#[test]
+fn schema_check_detects_shape_change() {
+    let check = InvariantSpec::new("schema", InvariantClass::Schema).with_check(checks::schema());
+
+    let before = serde_json::json!({ "a": 1, "b": { "c": "x" } });
+    let same = serde_json::json!({ "a": 2, "b": { "c": "y" } });
+    let changed = serde_json::json!({ "a": 2, "b": { "c": 3 } });
+
+    assert!(check.evaluate(&before, &same).is_ok());
+    assert_eq!(
+        check.evaluate(&before, &changed),
+        Err("schema changed at '/b/c'".to_string())
+    );
+}
+
+#[test]
+fn schema_check_detects_array_length_change() {
+    let check = InvariantSpec::new("schema", InvariantClass::Schema).with_check(checks::schema());
+
+    let before = serde_json::json!({ "items": [1, 2] });
+    let grown = serde_json::json!({ "items": [1, 2, 3] });
+    let shrunk = serde_json::json!({ "items": [] });
+
+    assert_eq!(
+        check.evaluate(&before, &grown),
+        Err("schema changed at '/items': length 2 -> 3".to_string())
+    );
+    assert_eq!(
+        check.evaluate(&before, &shrunk),
+        Err("schema changed at '/items': length 2 -> 0".to_string())
+    );
+}
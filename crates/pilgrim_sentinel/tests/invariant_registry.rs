@@ -1,4 +1,4 @@
-use pilgrim_sentinel::{InvariantRegistry, InvariantSpec, InvariantClass};
+use pilgrim_sentinel::{InvariantRegistry, InvariantSpec, InvariantClass, RegistryError};
 
 #[test]
 fn invariant_registry_registers_and_fetches() {
@@ -9,7 +9,7 @@ fn invariant_registry_registers_and_fetches() {
             "contract.state.transition",
             InvariantClass::Transition,
         )
-    );
+    ).unwrap();
 
     let inv = registry
         .get("contract.state.transition")
@@ -19,14 +19,35 @@ fn invariant_registry_registers_and_fetches() {
 }
 
 #[test]
-#[should_panic]
 fn invariant_registry_rejects_duplicates() {
     let mut registry = InvariantRegistry::new();
-registry.register(
-    InvariantSpec::new("dup", InvariantClass::Schema)
-);
-registry.register(
-    InvariantSpec::new("dup", InvariantClass::Schema)
-); // must panic
+    registry.register(
+        InvariantSpec::new("dup", InvariantClass::Schema)
+    ).unwrap();
+
+    let err = registry.register(
+        InvariantSpec::new("dup", InvariantClass::Value)
+    );
+
+    assert_eq!(err, Err(RegistryError::Duplicate { id: "dup" }));
+    // First registration wins
+    assert_eq!(registry.get("dup").unwrap().class, InvariantClass::Schema);
+}
+
+#[test]
+fn invariant_registry_filters_by_domain_in_id_order() {
+    let mut registry = InvariantRegistry::new();
+    for (id, class, domain) in [
+        ("risk.z", InvariantClass::Distribution, "risk"),
+        ("lab.a", InvariantClass::Temporal, "lab"),
+        ("risk.a", InvariantClass::Value, "risk"),
+    ] {
+        registry.register(InvariantSpec::new(id, class).in_domain(domain)).unwrap();
+    }
+
+    let risk: Vec<_> = registry.by_domain("risk").map(|s| s.id).collect();
+    assert_eq!(risk, ["risk.a", "risk.z"]);
 
+    let all: Vec<_> = registry.iter().map(|s| s.id).collect();
+    assert_eq!(all, ["lab.a", "risk.a", "risk.z"]);
 }
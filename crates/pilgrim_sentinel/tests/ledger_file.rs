@@ -0,0 +1,91 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use pilgrim_sentinel::ledger::DriftLedger;
+use pilgrim_sentinel::ledger_file::{LedgerFile, LedgerFileError};
+
+fn ledger_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "pilgrim_sentinel_{}_{}.jsonl",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn file_ledger_survives_reopen() {
+    let path = ledger_path("reopen");
+
+    {
+        let mut ledger = DriftLedger::open(&path).unwrap();
+        ledger.record("demo", "inv-001", "value", "a", "b");
+        ledger.record("demo", "inv-002", "value", "b", "c");
+    }
+
+    let mut ledger = DriftLedger::open(&path).unwrap();
+    assert_eq!(ledger.events().len(), 2);
+
+    // New events continue the persisted chain
+    let e3 = ledger.record("demo", "inv-003", "value", "c", "d");
+    assert_eq!(e3.seq, 2);
+    assert_eq!(e3.prev_event_hash.as_deref(), Some(ledger.events()[1].event_hash.as_str()));
+
+    let replay = LedgerFile::replay(&path).unwrap();
+    assert_eq!(replay.events, ledger.events());
+    assert_eq!(replay.truncated_tail, None);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn torn_tail_is_reported_not_dropped() {
+    let path = ledger_path("torn");
+
+    {
+        let mut ledger = DriftLedger::open(&path).unwrap();
+        ledger.record("demo", "inv-001", "value", "a", "b");
+    }
+    let intact = fs::metadata(&path).unwrap().len();
+
+    // Simulate a crash mid-append
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"{"seq":1,"domain":"de"#).unwrap();
+    drop(file);
+
+    // Post-mortem still reads the last complete violation
+    let replay = LedgerFile::replay(&path).unwrap();
+    assert_eq!(replay.events.len(), 1);
+    assert_eq!(replay.truncated_tail, Some((intact, 21)));
+
+    // Writers refuse to extend a torn file
+    assert!(matches!(
+        DriftLedger::open(&path),
+        Err(LedgerFileError::Truncated { offset, len: 21 }) if offset == intact
+    ));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn edited_record_breaks_replay() {
+    let path = ledger_path("edited");
+
+    {
+        let mut ledger = DriftLedger::open(&path).unwrap();
+        ledger.record("demo", "inv-001", "value", "a", "b");
+        ledger.record("demo", "inv-002", "value", "b", "c");
+    }
+
+    let text = fs::read_to_string(&path).unwrap();
+    fs::write(&path, text.replacen("inv-001", "inv-00X", 1)).unwrap();
+
+    assert!(matches!(
+        LedgerFile::replay(&path),
+        Err(LedgerFileError::Chain(_))
+    ));
+
+    fs::remove_file(&path).unwrap();
+}
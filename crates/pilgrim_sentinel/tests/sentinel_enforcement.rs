@@ -1,8 +1,10 @@
 use pilgrim_sentinel::{
     Sentinel,
     SentinelError,
+    SentinelPolicy,
 };
 
+use amethyst_invariants::Enforced;
 use pilgrim_sentinel::ledger::DriftLedger;
 use pilgrim_sentinel::invariants::{
     InvariantRegistry,
@@ -15,28 +17,115 @@ struct State {
     value: u32,
 }
 
-#[test]
-fn sentinel_detects_transition_drift() {
-    // --- registry ---
+fn registry() -> InvariantRegistry {
     let mut registry = InvariantRegistry::new();
     registry.register(
         InvariantSpec::new(
             "test::drift",
             InvariantClass::Transition,
         )
-    );
+    ).unwrap();
+    registry
+}
+
+#[test]
+fn sentinel_detects_transition_drift() {
+    // --- registry ---
+    let registry = registry();
 
     // --- ledger ---
     let mut ledger = DriftLedger::new();
 
     // --- states ---
     let s1 = State { value: 1 };
-    let s2 = State { value: 1 }; // identical => drift
+    let s2 = State { value: 2 }; // mutated => drift
 
     // --- before snapshot ---
-    let before = Sentinel::before(&s1, "test::drift");
+    let before = Sentinel::before(&s1);
 
     // --- enforce ---
     let spec = registry
         .get("test::drift")
-        .expect("
+        .expect("invariant missing");
+
+    let err = Sentinel::check(&before, &s2, "test", spec, &mut ledger)
+        .expect_err("drift must be reported");
+    assert_eq!(err.system_invariant(), "TRANS_002");
+
+    match err {
+        SentinelError::Drift(event) => {
+            assert_eq!(event.invariant, "test::drift");
+            assert_eq!(event.class, "transition");
+            assert_eq!(event.before_hash, before.hash);
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // Violation is recorded before it is surfaced
+    assert_eq!(ledger.events().len(), 1);
+}
+
+#[test]
+fn sentinel_passes_unchanged_state() {
+    let registry = registry();
+    let mut ledger = DriftLedger::new();
+
+    let s1 = State { value: 1 };
+    let before = Sentinel::before(&s1);
+
+    let spec = registry.get("test::drift").unwrap();
+    let token = Sentinel::after_with(
+        SentinelPolicy::Return,
+        &before,
+        &s1,
+        "test",
+        spec,
+        &mut ledger,
+    )
+    .expect("unchanged state must pass");
+
+    assert_eq!(token, before);
+    assert!(ledger.events().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "PILGRIM SENTINEL")]
+fn sentinel_panic_policy_panics_on_drift() {
+    let registry = registry();
+    let mut ledger = DriftLedger::new();
+
+    let before = Sentinel::before(&State { value: 1 });
+    let spec = registry.get("test::drift").unwrap();
+
+    let _ = Sentinel::after_with(
+        SentinelPolicy::Panic,
+        &before,
+        &State { value: 2 },
+        "test",
+        spec,
+        &mut ledger,
+    );
+}
+
+#[test]
+fn sentinel_enforcement_maps_to_system_invariants() {
+    use amethyst_invariants::CoverageReport;
+
+    let report = CoverageReport::new(&[pilgrim_sentinel::ENFORCEMENT]);
+
+    assert_eq!(report.unknown().count(), 0);
+    assert!(report.enforced().any(|inv| inv.id == "TRANS_002"));
+    assert_eq!(SentinelError::Unserializable.system_invariant(), "TRANS_002");
+
+    // Every declared point is one some error actually reports
+    let mut ledger = DriftLedger::new();
+    let event = Box::new(ledger.record("test", "test::drift", "transition", "a", "b"));
+    let errors = [
+        SentinelError::Drift(event.clone()),
+        SentinelError::Violated { event, reason: "broken".into() },
+        SentinelError::Unserializable,
+    ];
+    for point in pilgrim_sentinel::ENFORCEMENT {
+        assert!(errors.iter().any(|e| e.enforcement_point() == point), "{point:?} is never reported");
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::HashMap;
+
+use pilgrim_sentinel::{fingerprint, Sentinel};
+
+#[derive(serde::Serialize)]
+struct State {
+    value: u32,
+    labels: HashMap<String, u32>,
+}
+
+fn state(value: u32) -> State {
+    let mut labels = HashMap::new();
+    for (i, k) in ["alpha", "beta", "gamma", "delta"].iter().enumerate() {
+        labels.insert(k.to_string(), i as u32);
+    }
+    State { value, labels }
+}
+
+#[test]
+fn identical_states_share_a_fingerprint() {
+    // HashMap iteration order differs between instances; the digest must not.
+    assert_eq!(Sentinel::before(&state(1)), Sentinel::before(&state(1)));
+}
+
+#[test]
+fn silent_mutation_changes_the_fingerprint() {
+    let mut s = state(1);
+    let before = Sentinel::before(&s);
+
+    s.value = 999;
+
+    assert_ne!(before.hash, fingerprint(&s).unwrap());
+}
+
+#[test]
+fn fingerprint_is_hex_sha256() {
+    let hash = fingerprint(&state(1)).unwrap();
+    assert_eq!(hash.len(), 64);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+}